@@ -0,0 +1,394 @@
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                                                                            │ *
+ * │ This Source Code Form is subject to the terms of the Mozilla Public                        │ *
+ * │ License, v. 2.0. If a copy of the MPL was not distributed with this                        │ *
+ * │ file, You can obtain one at http://mozilla.org/MPL/2.0/.                                   │ *
+ * │                                                                                            │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                            use                                             │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+use super::{TTL, ROOT_INO};
+use super::projects::Projects;
+use fuser::{FileAttr, FileType, ReplyAttr, ReplyData, ReplyDirectory, ReplyEntry};
+use libc::ENOENT;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                           const                                            │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+pub const INO: u64 = 1 << 47;
+pub const STR: &str = "artifacts";
+
+const EXAMPLES: &str = "examples";
+const TESTS: &str = "tests";
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                         Artifacts                                          │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+pub struct Artifacts {
+    ino: u64,
+    by_name: HashMap<(u64, String), u64>,
+    by_ino: HashMap<u64, Node>,
+    target: Option<PathBuf>,
+}
+
+impl Artifacts {
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       new()                                        │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn new() -> Self {
+        Artifacts {
+            ino: INO,
+            by_name: HashMap::new(),
+            by_ino: HashMap::new(),
+            target: None,
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                    configure()                                     │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn configure(&mut self, target: Option<PathBuf>) {
+        self.target = target;
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     inherit()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn inherit(&mut self, old: &Self) {
+        self.ino = self.ino.max(old.ino);
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     getattr()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn getattr(&self, ino: u64, reply: ReplyAttr) {
+        if let Some(attr) = self.attr(ino) {
+            reply.attr(&TTL, &attr);
+        } else {
+            reply.error(ENOENT);
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      lookup()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn lookup(&mut self, projects: &Projects, parent: u64, name: &str, reply: ReplyEntry) {
+        if parent == ROOT_INO {
+            return reply.entry(&TTL, &self.attr(INO).unwrap(), 0);
+        }
+
+        let node = match self.children(projects, parent) {
+            Some(children) => children.into_iter().find(|(child, _)| child == name),
+            None => None,
+        };
+
+        match node {
+            Some((_, node)) => {
+                let ino = self.add_node(parent, name, node);
+                reply.entry(&TTL, &self.attr(ino).unwrap(), 0);
+            },
+            None => reply.error(ENOENT),
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     readdir()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn readdir(&mut self, projects: &Projects, ino: u64, offset: usize, mut reply: ReplyDirectory) {
+        let children = match self.children(projects, ino) {
+            Some(children) => children,
+            None => return reply.error(ENOENT),
+        };
+
+        for (idx, (name, node)) in children.into_iter().enumerate().skip(offset) {
+            let kind = node.kind();
+            let entry = self.add_node(ino, &name, node);
+            if reply.add(entry, (idx + 1) as i64, kind, name) {
+                break;
+            }
+        }
+
+        reply.ok();
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     readlink()                                     │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn readlink(&self, ino: u64, reply: ReplyData) {
+        match self.by_ino.get(&ino) {
+            Some(Node::Artifact(path, _)) => reply.data(path.as_os_str().as_bytes()),
+            _ => reply.error(ENOENT),
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     children()                                     │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn children(&self, projects: &Projects, ino: u64) -> Option<Vec<(String, Node)>> {
+        let mut children = match self.by_ino.get(&ino) {
+            _ if ino == INO => projects
+                .iter()
+                .filter(|(_, path)| self.target_dir(path).is_dir())
+                .map(|(name, _)| (name.to_string(), Node::Project(name.to_string())))
+                .collect(),
+            Some(Node::Project(name)) => profiles(&self.target_dir(projects.get(name)?))
+                .into_iter()
+                .map(|(name, dir)| (name, Node::Profile(dir)))
+                .collect(),
+            Some(Node::Profile(dir)) => {
+                let mut children = binaries(dir);
+                if dir.join(EXAMPLES).is_dir() {
+                    children.push((EXAMPLES.into(), Node::Examples(dir.join(EXAMPLES))));
+                }
+                if dir.join("deps").is_dir() {
+                    children.push((TESTS.into(), Node::Tests(dir.join("deps"))));
+                }
+
+                children
+            },
+            Some(Node::Examples(dir)) => newest(dir, false),
+            Some(Node::Tests(dir)) => newest(dir, true),
+            _ => return None,
+        };
+
+        children.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+
+        Some(children)
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                    target_dir()                                    │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    // The configured directory wins over the daemon's own `CARGO_TARGET_DIR`, which is only a
+    // fallback as it rarely matches the environment projects actually get built in.
+    pub fn target_dir(&self, project: &Path) -> PathBuf {
+        if let Some(target) = &self.target {
+            return project.join(target);
+        } else if let Some(target) = std::env::var_os("CARGO_TARGET_DIR") {
+            return project.join(target);
+        }
+
+        for dir in project.ancestors() {
+            for config in &[".cargo/config.toml", ".cargo/config"] {
+                let target = fs::read(dir.join(config))
+                    .ok()
+                    .and_then(|config| toml::from_slice::<toml::Value>(&config).ok())
+                    .and_then(|config| {
+                        Some(config.get("build")?.get("target-dir")?.as_str()?.to_string())
+                    });
+
+                if let Some(target) = target {
+                    return dir.join(target);
+                }
+            }
+        }
+
+        project.join("target")
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     add_node()                                     │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn add_node(&mut self, parent: u64, name: &str, node: Node) -> u64 {
+        let ino = if let Some(ino) = self.by_name.get(&(parent, name.to_string())) {
+            *ino
+        } else {
+            self.ino += 1;
+            self.by_name.insert((parent, name.to_string()), self.ino);
+            self.ino
+        };
+
+        self.by_ino.insert(ino, node);
+
+        ino
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       attr()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn attr(&self, ino: u64) -> Option<FileAttr> {
+        let (kind, time) = match self.by_ino.get(&ino) {
+            _ if ino == INO => (FileType::Directory, UNIX_EPOCH),
+            Some(Node::Artifact(_, time)) => (FileType::Symlink, *time),
+            Some(node) => (node.kind(), UNIX_EPOCH),
+            None => return None,
+        };
+
+        Some(FileAttr {
+            ino,
+            size: 0,
+            blocks: 0,
+            atime: time,
+            mtime: time,
+            ctime: time,
+            crtime: UNIX_EPOCH,
+            kind,
+            perm: 0o555,
+            nlink: if kind == FileType::Directory { 2 } else { 1 },
+            uid: 0,
+            gid: 0,
+            rdev: 0,
+            blksize: 0,
+            padding: 0,
+            flags: 0,
+        })
+    }
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                            Node                                            │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+enum Node {
+    Project(String),
+    Profile(PathBuf),
+    Examples(PathBuf),
+    Tests(PathBuf),
+    Artifact(PathBuf, SystemTime),
+}
+
+impl Node {
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       kind()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn kind(&self) -> FileType {
+        match self {
+            Node::Artifact(..) => FileType::Symlink,
+            _ => FileType::Directory,
+        }
+    }
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                         profiles()                                         │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+fn profiles(target: &Path) -> Vec<(String, PathBuf)> {
+    let mut profiles = Vec::new();
+
+    for (name, dir) in dirs(target) {
+        if dir.join("deps").is_dir() {
+            profiles.push((name, dir));
+        } else {
+            for (profile, dir) in dirs(&dir) {
+                if dir.join("deps").is_dir() {
+                    profiles.push((format!("{}@{}", profile, name), dir));
+                }
+            }
+        }
+    }
+
+    profiles
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                           dirs()                                           │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+fn dirs(dir: &Path) -> Vec<(String, PathBuf)> {
+    fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().map(|kind| kind.is_dir()).unwrap_or(false))
+        .filter_map(|entry| Some((entry.file_name().into_string().ok()?, entry.path())))
+        .collect()
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                        binaries()                                          │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+fn binaries(dir: &Path) -> Vec<(String, Node)> {
+    executables(dir)
+        .into_iter()
+        .map(|(name, path, modified)| (name, Node::Artifact(path, modified)))
+        .collect()
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                          newest()                                          │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+fn newest(dir: &Path, hashed_only: bool) -> Vec<(String, Node)> {
+    let mut newest = BTreeMap::<String, (PathBuf, SystemTime)>::new();
+
+    for (name, path, modified) in executables(dir) {
+        let name = match unhash(&name) {
+            Some(name) => name.to_string(),
+            None if hashed_only => continue,
+            None => name,
+        };
+
+        match newest.get(&name) {
+            Some((_, newer)) if *newer >= modified => (),
+            _ => {
+                newest.insert(name, (path, modified));
+            },
+        }
+    }
+
+    newest
+        .into_iter()
+        .map(|(name, (path, modified))| (name, Node::Artifact(path, modified)))
+        .collect()
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                       executables()                                        │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+fn executables(dir: &Path) -> Vec<(String, PathBuf, SystemTime)> {
+    fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let name = entry.file_name().into_string().ok()?;
+            let metadata = entry.metadata().ok()?;
+
+            if !metadata.is_file() || metadata.permissions().mode() & 0o111 == 0 || name.contains('.') {
+                return None;
+            }
+
+            Some((name, entry.path(), metadata.modified().unwrap_or(UNIX_EPOCH)))
+        })
+        .collect()
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                          unhash()                                          │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+fn unhash(name: &str) -> Option<&str> {
+    let (name, hash) = name.rsplit_once('-')?;
+
+    if hash.len() == 16 && hash.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+        Some(name)
+    } else {
+        None
+    }
+}
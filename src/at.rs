@@ -0,0 +1,363 @@
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                                                                            │ *
+ * │ This Source Code Form is subject to the terms of the Mozilla Public                        │ *
+ * │ License, v. 2.0. If a copy of the MPL was not distributed with this                        │ *
+ * │ file, You can obtain one at http://mozilla.org/MPL/2.0/.                                   │ *
+ * │                                                                                            │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                            use                                             │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+use super::{TTL, ROOT_INO};
+use super::projects::Projects;
+use fuser::{FileAttr, FileType, ReplyAttr, ReplyData, ReplyDirectory, ReplyEntry};
+use git2::{ObjectType, Oid, Repository, TreeEntry};
+use libc::{EIO, ENOENT};
+use std::collections::{HashMap, VecDeque};
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                           const                                            │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+pub const INO: u64 = 1 << 49;
+pub const STR: &str = "at";
+
+const BLOBS: usize = 16;
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                             At                                             │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+pub struct At {
+    ino: u64,
+    by_name: HashMap<(u64, String), u64>,
+    by_ino: HashMap<u64, Node>,
+    missing: HashMap<String, SystemTime>,
+    blobs: VecDeque<(Oid, Vec<u8>)>,
+}
+
+impl At {
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       new()                                        │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn new() -> Self {
+        At {
+            ino: INO,
+            by_name: HashMap::new(),
+            by_ino: HashMap::new(),
+            missing: HashMap::new(),
+            blobs: VecDeque::new(),
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     inherit()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn inherit(&mut self, old: &Self) {
+        self.ino = self.ino.max(old.ino);
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     getattr()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn getattr(&self, ino: u64, reply: ReplyAttr) {
+        if let Some(attr) = self.attr(ino) {
+            reply.attr(&TTL, &attr);
+        } else {
+            reply.error(ENOENT);
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      lookup()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn lookup(&mut self, projects: &Projects, parent: u64, name: &str, reply: ReplyEntry) {
+        let node = if parent == ROOT_INO {
+            return reply.entry(&TTL, &self.attr(INO).unwrap(), 0);
+        } else if parent == INO {
+            // Every project is searched for the revision, so names that aren't one anywhere are
+            // remembered for a while instead of being searched for again on each probe.
+            let missing = self.missing.get(name).and_then(|at| at.elapsed().ok());
+            if matches!(missing, Some(elapsed) if elapsed < TTL) {
+                return reply.error(ENOENT);
+            }
+
+            let rev = name.replace(':', "/");
+            if !projects.iter().any(|(_, path)| revision(path, &rev).is_some()) {
+                self.missing.retain(|_, at| at.elapsed().unwrap_or_default() < TTL);
+                self.missing.insert(name.to_string(), SystemTime::now());
+                return reply.error(ENOENT);
+            }
+
+            Node::Rev(rev)
+        } else {
+            match self.by_ino.get(&parent) {
+                Some(Node::Rev(rev)) => {
+                    let path = match projects.get(name) {
+                        Some(path) => path,
+                        None => return reply.error(ENOENT),
+                    };
+
+                    match revision(path, rev) {
+                        Some((oid, time)) => Node::Tree { project: name.to_string(), oid, time },
+                        None => return reply.error(ENOENT),
+                    }
+                },
+                Some(Node::Tree { project, oid, time }) => {
+                    let repo = match projects.get(project).map(Repository::open) {
+                        Some(Ok(repo)) => repo,
+                        _ => return reply.error(EIO),
+                    };
+
+                    let tree = match repo.find_tree(*oid) {
+                        Ok(tree) => tree,
+                        Err(_) => return reply.error(EIO),
+                    };
+
+                    match tree.get_name(name).and_then(|entry| node(&repo, project, &entry, *time)) {
+                        Some(node) => node,
+                        None => return reply.error(ENOENT),
+                    }
+                },
+                _ => return reply.error(ENOENT),
+            }
+        };
+
+        let ino = self.add_node(parent, name, node);
+        reply.entry(&TTL, &self.attr(ino).unwrap(), 0);
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     readdir()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn readdir(&mut self, projects: &Projects, ino: u64, offset: usize, mut reply: ReplyDirectory) {
+        let entries = match self.by_ino.get(&ino) {
+            _ if ino == INO => Vec::new(),
+            Some(Node::Rev(rev)) => {
+                let mut entries = projects
+                    .iter()
+                    .filter_map(|(name, path)| {
+                        let (oid, time) = revision(path, rev)?;
+                        Some((name.to_string(), Node::Tree { project: name.to_string(), oid, time }))
+                    })
+                    .collect::<Vec<_>>();
+                entries.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+
+                entries
+            },
+            Some(Node::Tree { project, oid, time }) => {
+                let repo = match projects.get(project).map(Repository::open) {
+                    Some(Ok(repo)) => repo,
+                    _ => return reply.error(EIO),
+                };
+
+                let tree = match repo.find_tree(*oid) {
+                    Ok(tree) => tree,
+                    Err(_) => return reply.error(EIO),
+                };
+
+                tree.iter()
+                    .filter_map(|entry| {
+                        let name = entry.name()?.to_string();
+                        Some((name, node(&repo, project, &entry, *time)?))
+                    })
+                    .collect()
+            },
+            _ => return reply.error(ENOENT),
+        };
+
+        for (idx, (name, node)) in entries.into_iter().enumerate().skip(offset) {
+            let kind = node.kind();
+            let entry = self.add_node(ino, &name, node);
+            if reply.add(entry, (idx + 1) as i64, kind, name) {
+                break;
+            }
+        }
+
+        reply.ok();
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     readlink()                                     │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn readlink(&mut self, projects: &Projects, ino: u64, reply: ReplyData) {
+        match self.by_ino.get(&ino) {
+            Some(Node::Blob { link: true, .. }) => self.read(projects, ino, 0, u32::MAX, reply),
+            _ => reply.error(ENOENT),
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       read()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    // Files are read in chunks, so the last few blobs are kept around instead of opening the
+    // repository and copying the whole blob again for each one.
+    pub fn read(
+        &mut self,
+        projects: &Projects,
+        ino: u64,
+        offset: i64,
+        size: u32,
+        reply: ReplyData,
+    ) {
+        let (project, oid) = match self.by_ino.get(&ino) {
+            Some(Node::Blob { project, oid, .. }) => (project, *oid),
+            _ => return reply.error(ENOENT),
+        };
+
+        if !self.blobs.iter().any(|(cached, _)| *cached == oid) {
+            let blob = projects
+                .get(project)
+                .and_then(|path| Repository::open(path).ok())
+                .and_then(|repo| Some(repo.find_blob(oid).ok()?.content().to_vec()));
+
+            match blob {
+                Some(data) => self.blobs.push_front((oid, data)),
+                None => return reply.error(EIO),
+            }
+
+            self.blobs.truncate(BLOBS);
+        }
+
+        let data = &self.blobs.iter().find(|(cached, _)| *cached == oid).unwrap().1;
+        let start = (offset.max(0) as usize).min(data.len());
+        let end = start.saturating_add(size as usize).min(data.len());
+        reply.data(&data[start..end]);
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     add_node()                                     │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn add_node(&mut self, parent: u64, name: &str, node: Node) -> u64 {
+        let ino = if let Some(ino) = self.by_name.get(&(parent, name.to_string())) {
+            *ino
+        } else {
+            self.ino += 1;
+            self.by_name.insert((parent, name.to_string()), self.ino);
+            self.ino
+        };
+
+        self.by_ino.insert(ino, node);
+
+        ino
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       attr()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn attr(&self, ino: u64) -> Option<FileAttr> {
+        let (kind, size, perm, time) = match self.by_ino.get(&ino) {
+            _ if ino == INO => (FileType::Directory, 0, 0o555, UNIX_EPOCH),
+            Some(Node::Rev(_)) => (FileType::Directory, 0, 0o555, UNIX_EPOCH),
+            Some(node @ Node::Tree { time, .. }) => (node.kind(), 0, 0o555, *time),
+            Some(node @ Node::Blob { size, perm, time, .. }) => (node.kind(), *size, *perm, *time),
+            None => return None,
+        };
+
+        Some(FileAttr {
+            ino,
+            size,
+            blocks: 0,
+            atime: time,
+            mtime: time,
+            ctime: time,
+            crtime: UNIX_EPOCH,
+            kind,
+            perm,
+            nlink: if kind == FileType::Directory { 2 } else { 1 },
+            uid: 0,
+            gid: 0,
+            rdev: 0,
+            blksize: 0,
+            padding: 0,
+            flags: 0,
+        })
+    }
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                            Node                                            │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+enum Node {
+    Rev(String),
+    Tree {
+        project: String,
+        oid: Oid,
+        time: SystemTime,
+    },
+    Blob {
+        project: String,
+        oid: Oid,
+        size: u64,
+        perm: u16,
+        link: bool,
+        time: SystemTime,
+    },
+}
+
+impl Node {
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       kind()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn kind(&self) -> FileType {
+        match self {
+            Node::Rev(_) | Node::Tree { .. } => FileType::Directory,
+            Node::Blob { link: true, .. } => FileType::Symlink,
+            Node::Blob { .. } => FileType::RegularFile,
+        }
+    }
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                         revision()                                         │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+fn revision(path: &Path, rev: &str) -> Option<(Oid, SystemTime)> {
+    let repo = Repository::open(path).ok()?;
+    let commit = repo.revparse_single(rev).ok()?.peel_to_commit().ok()?;
+    let time = UNIX_EPOCH + Duration::from_secs(commit.time().seconds().max(0) as u64);
+
+    Some((commit.tree_id(), time))
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                           node()                                           │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+fn node(repo: &Repository, project: &str, entry: &TreeEntry, time: SystemTime) -> Option<Node> {
+    match entry.kind()? {
+        ObjectType::Tree => Some(Node::Tree {
+            project: project.to_string(),
+            oid: entry.id(),
+            time,
+        }),
+        ObjectType::Blob => {
+            let (size, _) = repo.odb().ok()?.read_header(entry.id()).ok()?;
+
+            Some(Node::Blob {
+                project: project.to_string(),
+                oid: entry.id(),
+                size: size as u64,
+                perm: if entry.filemode() & 0o111 != 0 { 0o555 } else { 0o444 },
+                link: entry.filemode() == 0o120000,
+                time,
+            })
+        },
+        _ => None,
+    }
+}
@@ -10,22 +10,113 @@
  * │                                            use                                             │ *
 \* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
 
-use super::FileSystem;
-use regex::Regex;
-use serde::Deserialize;
-use std::collections::HashMap;
+use super::{FileSystem, ENTRIES};
+use super::mirrors::{Bases, Collision, Filters, Layout, Renaming, Stale, Transform, Versions};
+use super::recent::Activity;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 /* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
  * │                                           Config                                           │ *
 \* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Serialize)]
 pub struct Config {
     projects: Vec<Project>,
     mirrors: Vec<Mirror>,
+    #[serde(default)]
+    providers: Vec<Provider>,
+    #[serde(default)]
+    passthrough: bool,
+    #[serde(default)]
+    recent: Recent,
+    #[serde(default)]
+    frequent: Frequent,
+    #[serde(default)]
+    git: Git,
+    #[serde(default)]
+    worktrees: Worktrees,
+    #[serde(default)]
+    lang: Lang,
+    #[serde(default)]
+    groups: HashMap<String, Group>,
+    #[serde(default)]
+    search: Search,
+    #[serde(default)]
+    usage: Usage,
+    #[serde(default)]
+    artifacts: Artifacts,
 }
 
 impl Config {
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       read()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn read(path: &Path) -> Result<Self, String> {
+        let config = fs::read(path).map_err(|err| format!("{}: {}", path.display(), err))?;
+        let config = toml::from_slice::<Config>(&config)
+            .map_err(|err| format!("{}: {}", path.display(), err))?;
+
+        config.check()?;
+
+        Ok(config)
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      check()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn check(&self) -> Result<(), String> {
+        let mut names = HashSet::new();
+        for project in &self.projects {
+            fs::metadata(&project.path).map_err(|err| format!("{}: {}", project.path, err))?;
+
+            for name in std::iter::once(&project.name).chain(&project.aliases) {
+                if !names.insert(name) {
+                    return Err(format!("project {} already exists", name));
+                }
+            }
+        }
+
+        let mut names = HashSet::new();
+        for mirror in &self.mirrors {
+            mirror.bases()?;
+            mirror.layout(&mirror.renaming()?)?;
+
+            for name in std::iter::once(&mirror.name).chain(&mirror.aliases) {
+                if !names.insert(name) {
+                    return Err(format!("mirror {} already exists", name));
+                }
+            }
+        }
+
+        // Providers are mounted next to the built-in namespaces, which would shadow them.
+        let mut names = ENTRIES.iter().map(|(_, name)| *name).collect::<HashSet<_>>();
+        for provider in &self.providers {
+            if provider.name.is_empty() || provider.name.contains('/') {
+                return Err(format!("invalid provider name: {:?}", provider.name));
+            } else if !names.insert(&provider.name) {
+                return Err(format!("provider {} already exists", provider.name));
+            }
+        }
+
+        Ok(())
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      render()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn render(&self) -> String {
+        toml::Value::try_from(self)
+            .and_then(|config| toml::to_string(&config))
+            .unwrap_or_else(|err| format!("# failed to render config: {}\n", err))
+    }
+
 /*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
  *     │                                    load_into()                                     │     *
 \*     └────────────────────────────────────────────────────────────────────────────────────┘     */
@@ -33,9 +124,38 @@ impl Config {
     pub fn load_into(self, fs: &mut FileSystem) {
         let mut groups = HashMap::new();
 
+        fs.set_recent(
+            self.recent.count,
+            self.recent.activity,
+            self.recent.scan,
+            Duration::from_secs(self.recent.interval),
+        );
+        fs.set_frequent(self.frequent.count, self.frequent.ignore);
+        fs.set_git(Duration::from_secs(self.git.interval));
+        fs.set_worktrees(self.worktrees.location);
+        fs.set_search(self.search.paths);
+        fs.set_artifacts(self.artifacts.target_dir);
+        fs.set_usage(self.usage.budget, Duration::from_secs(self.usage.interval), self.usage.top);
+
+        let defaults = self.groups;
         for project in self.projects {
             std::fs::metadata(&project.path).unwrap();
 
+            let mut links = BTreeMap::new();
+            for group in project.groups.iter().filter_map(|group| defaults.get(group)) {
+                links.extend(group.links.clone());
+            }
+            links.extend(project.links);
+            fs.add_links(project.name.clone(), links);
+
+            fs.add_info(
+                project.name.clone(),
+                project.path.clone(),
+                project.aliases.clone(),
+                project.groups.clone(),
+                project.tags,
+            );
+
             let pgroups = project.groups.into_iter().map(|group| {
                 if let Some(ino) = groups.get(&group) {
                     *ino
@@ -51,19 +171,41 @@ impl Config {
                 project.path,
                 pgroups.into_iter(),
                 &project.aliases,
+                project.passthrough.unwrap_or(self.passthrough),
             );
         }
 
-        for mirror in self.mirrors {
-            std::fs::metadata(&mirror.path).unwrap();
+        fs.set_languages(self.lang.markers);
+        fs.scan_remotes();
 
+        for mirror in self.mirrors {
+            let bases = mirror.bases().unwrap();
+            let renaming = mirror.renaming().unwrap();
+            let layout = mirror.layout(&renaming).unwrap();
             fs.add_mirror(
-                mirror.name,
-                mirror.path,
-                Regex::new(&mirror.renaming).unwrap(),
+                mirror.name.clone(),
+                bases,
+                renaming,
                 &mirror.aliases,
+                mirror.passthrough.unwrap_or(self.passthrough),
+                layout,
+            );
+
+            if let Some(stale) = mirror.stale {
+                fs.set_mirror_stale(&mirror.name, stale);
+            }
+        }
+
+        for provider in self.providers {
+            fs.add_provider(
+                provider.name,
+                provider.command,
+                Duration::from_secs(provider.ttl.unwrap_or(60)),
+                Duration::from_secs(provider.timeout.unwrap_or(10)),
             );
         }
+
+        fs.scan_usage();
     }
 }
 
@@ -71,22 +213,223 @@ impl Config {
  * │                                          Project                                           │ *
 \* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Serialize)]
 struct Project {
     name: String,
     path: String,
     groups: Vec<String>,
     aliases: Vec<String>,
+    passthrough: Option<bool>,
+    #[serde(default)]
+    links: BTreeMap<String, String>,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                           Group                                            │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+#[derive(Deserialize, Serialize)]
+struct Group {
+    #[serde(default)]
+    links: BTreeMap<String, String>,
 }
 
 /* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
  * │                                           Mirror                                           │ *
 \* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Serialize)]
 struct Mirror {
     name: String,
     path: String,
+    #[serde(default)]
+    paths: Vec<String>,
+    depth: Option<usize>,
     renaming: String,
+    rename_to: Option<String>,
+    #[serde(default)]
+    transforms: Vec<Transform>,
     aliases: Vec<String>,
+    passthrough: Option<bool>,
+    collisions: Option<Collision>,
+    versions: Option<Versions>,
+    #[serde(default)]
+    filters: Filters,
+    stale: Option<Stale>,
+}
+
+impl Mirror {
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      bases()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn bases(&self) -> Result<Bases, String> {
+        let patterns = std::iter::once(&self.path).chain(&self.paths).cloned().collect();
+        Bases::new(patterns, self.depth.unwrap_or(1), &self.filters)
+            .map_err(|err| format!("{}: {}", self.name, err))
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     renaming()                                     │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn renaming(&self) -> Result<Renaming, String> {
+        Renaming::new(&self.renaming, self.rename_to.clone(), self.transforms.clone())
+            .map_err(|err| format!("{}: {}", self.name, err))
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      layout()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn layout(&self, renaming: &Renaming) -> Result<Layout, String> {
+        match &self.versions {
+            Some(_) if self.collisions.is_some() => {
+                Err(format!("{}: `collisions` and `versions` are mutually exclusive", self.name))
+            },
+            Some(versions) => {
+                versions.check(renaming).map_err(|err| format!("{}: {}", self.name, err))?;
+                Ok(Layout::Versioned(versions.clone()))
+            },
+            None => Ok(Layout::Collapsed(self.collisions.unwrap_or(Collision::Newest))),
+        }
+    }
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                          Provider                                          │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+#[derive(Deserialize, Serialize)]
+struct Provider {
+    name: String,
+    command: String,
+    ttl: Option<u64>,
+    timeout: Option<u64>,
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                           Recent                                           │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+#[derive(Deserialize, Serialize)]
+#[serde(default)]
+struct Recent {
+    count: usize,
+    activity: Activity,
+    scan: usize,
+    interval: u64,
+}
+
+impl Default for Recent {
+    fn default() -> Self {
+        Recent {
+            count: 10,
+            activity: Activity::Mtime,
+            scan: 10_000,
+            interval: 10,
+        }
+    }
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                          Frequent                                          │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+#[derive(Deserialize, Serialize)]
+#[serde(default)]
+struct Frequent {
+    count: usize,
+    ignore: Vec<String>,
+}
+
+impl Default for Frequent {
+    fn default() -> Self {
+        Frequent {
+            count: 10,
+            ignore: Vec::new(),
+        }
+    }
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                            Git                                             │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+#[derive(Deserialize, Serialize)]
+#[serde(default)]
+struct Git {
+    interval: u64,
+}
+
+impl Default for Git {
+    fn default() -> Self {
+        Git {
+            interval: 30,
+        }
+    }
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                         Worktrees                                          │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+#[derive(Default, Deserialize, Serialize)]
+#[serde(default)]
+struct Worktrees {
+    location: Option<PathBuf>,
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                            Lang                                            │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+#[derive(Default, Deserialize, Serialize)]
+#[serde(default)]
+struct Lang {
+    markers: HashMap<String, String>,
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                           Search                                           │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+#[derive(Default, Deserialize, Serialize)]
+#[serde(default)]
+struct Search {
+    paths: bool,
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                         Artifacts                                          │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+#[derive(Default, Deserialize, Serialize)]
+#[serde(default)]
+struct Artifacts {
+    target_dir: Option<PathBuf>,
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                           Usage                                            │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+#[derive(Deserialize, Serialize)]
+#[serde(default)]
+struct Usage {
+    budget: usize,
+    interval: u64,
+    top: usize,
+}
+
+impl Default for Usage {
+    fn default() -> Self {
+        Usage {
+            budget: 2_000,
+            interval: 600,
+            top: 20,
+        }
+    }
 }
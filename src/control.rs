@@ -0,0 +1,364 @@
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                                                                            │ *
+ * │ This Source Code Form is subject to the terms of the Mozilla Public                        │ *
+ * │ License, v. 2.0. If a copy of the MPL was not distributed with this                        │ *
+ * │ file, You can obtain one at http://mozilla.org/MPL/2.0/.                                   │ *
+ * │                                                                                            │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                            use                                             │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+use super::{TTL, ROOT_INO};
+use super::crates::Crates;
+use super::deps::Deps;
+use super::frequent::Frequent;
+use super::git::Git;
+use super::groups::Groups;
+use super::lang::Languages;
+use super::links::Links;
+use super::mirrors::Mirrors;
+use super::projects::Projects;
+use super::recent::Recent;
+use fuser::{FileAttr, FileType, ReplyAttr, ReplyData, ReplyDirectory, ReplyEntry};
+use libc::ENOENT;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                           const                                            │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+pub const INO: u64 = 1 << 50;
+pub const STR: &str = ".pr0j3c75";
+
+const FILES: [&str; 5] = ["status", "config", "index.json", "reload", "rescan-mirrors"];
+
+const STATUS_INO: u64 = INO + 1;
+const CONFIG_INO: u64 = INO + 2;
+const INDEX_INO: u64 = INO + 3;
+const RELOAD_INO: u64 = INO + 4;
+const RESCAN_MIRRORS_INO: u64 = INO + 5;
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                          Trigger                                           │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+pub enum Trigger {
+    Reload,
+    RescanMirrors,
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                          Control                                           │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+pub struct Control {
+    path: PathBuf,
+    writable: bool,
+    started: SystemTime,
+    config: String,
+    reloaded: Option<(SystemTime, Result<(), String>)>,
+}
+
+impl Control {
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       new()                                        │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn new(path: PathBuf, writable: bool) -> Self {
+        Control {
+            path,
+            writable,
+            started: SystemTime::now(),
+            config: String::new(),
+            reloaded: None,
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       path()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     writable()                                     │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn writable(&self) -> bool {
+        self.writable
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     reloaded()                                     │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn reloaded(&mut self, result: Result<String, String>) {
+        let result = result.map(|config| self.config = config);
+        self.reloaded = Some((SystemTime::now(), result));
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     trigger()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn trigger(&self, ino: u64) -> Option<Trigger> {
+        match ino {
+            RELOAD_INO => Some(Trigger::Reload),
+            RESCAN_MIRRORS_INO => Some(Trigger::RescanMirrors),
+            _ => None,
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     getattr()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn getattr(&self, namespace: Namespace, ino: u64, reply: ReplyAttr) {
+        if let Some(attr) = self.attr(namespace, ino) {
+            reply.attr(&TTL, &attr);
+        } else {
+            reply.error(ENOENT);
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      lookup()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn lookup(
+        &self,
+        namespace: Namespace,
+        parent: u64,
+        name: &str,
+        reply: ReplyEntry,
+    ) {
+        let ino = if parent == ROOT_INO {
+            Some(INO)
+        } else if parent == INO {
+            FILES.iter().position(|file| *file == name).map(|idx| INO + 1 + idx as u64)
+        } else {
+            None
+        };
+
+        match ino.and_then(|ino| self.attr(namespace, ino)) {
+            Some(attr) => reply.entry(&TTL, &attr, 0),
+            None => reply.error(ENOENT),
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     readdir()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn readdir(&self, ino: u64, offset: usize, mut reply: ReplyDirectory) {
+        if ino != INO {
+            return reply.error(ENOENT);
+        }
+
+        for (idx, name) in FILES.iter().enumerate().skip(offset) {
+            if reply.add(INO + 1 + idx as u64, (idx + 1) as i64, FileType::RegularFile, name) {
+                break;
+            }
+        }
+
+        reply.ok();
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       read()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn read(
+        &self,
+        namespace: Namespace,
+        ino: u64,
+        offset: i64,
+        size: u32,
+        reply: ReplyData,
+    ) {
+        match self.data(namespace, ino) {
+            Some(data) => {
+                let start = (offset.max(0) as usize).min(data.len());
+                let end = (start + size as usize).min(data.len());
+                reply.data(&data[start..end]);
+            },
+            None => reply.error(ENOENT),
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       data()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn data(&self, namespace: Namespace, ino: u64) -> Option<Vec<u8>> {
+        match ino {
+            STATUS_INO => Some(self.status(namespace).into_bytes()),
+            CONFIG_INO => Some(self.config.clone().into_bytes()),
+            INDEX_INO => {
+                let mut index = serde_json::to_vec_pretty(&Index::new(namespace)).ok()?;
+                index.push(b'\n');
+                Some(index)
+            },
+            RELOAD_INO | RESCAN_MIRRORS_INO => Some(Vec::new()),
+            _ => None,
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      status()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn status(&self, Namespace { projects, groups, mirrors, .. }: Namespace) -> String {
+        let mirrors = mirrors.index();
+        let reloaded = match &self.reloaded {
+            Some((at, Ok(()))) => format!("ok ({})", secs(*at)),
+            Some((at, Err(err))) => format!("error ({}): {}", secs(*at), err),
+            None => "never".into(),
+        };
+
+        // The mount itself is always writable, so that the triggers are.
+        let mount = if self.writable { "read-write" } else { "read-only (except triggers)" };
+
+        format!(
+            "uptime: {}\n\
+             config: {}\n\
+             mount: {}\n\
+             projects: {}\n\
+             aliases: {}\n\
+             groups: {}\n\
+             mirrors: {}\n\
+             mirror entries: {}\n\
+             last reload: {}\n",
+            self.started.elapsed().unwrap_or_default().as_secs(),
+            self.path.display(),
+            mount,
+            projects.iter().count(),
+            projects.entries().count() - projects.iter().count(),
+            groups.iter().count(),
+            mirrors.len(),
+            mirrors.values().map(BTreeMap::len).sum::<usize>(),
+            reloaded,
+        )
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       attr()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn attr(&self, namespace: Namespace, ino: u64) -> Option<FileAttr> {
+        let (kind, size, perm, nlink) = match ino {
+            INO => (FileType::Directory, 0, 0o555, 2),
+            RELOAD_INO | RESCAN_MIRRORS_INO => (FileType::RegularFile, 0, 0o644, 1),
+            _ => (FileType::RegularFile, self.data(namespace, ino)?.len() as u64, 0o444, 1),
+        };
+
+        Some(FileAttr {
+            ino,
+            size,
+            blocks: 0,
+            atime: UNIX_EPOCH,
+            mtime: UNIX_EPOCH,
+            ctime: UNIX_EPOCH,
+            crtime: UNIX_EPOCH,
+            kind,
+            perm,
+            nlink,
+            uid: 0,
+            gid: 0,
+            rdev: 0,
+            blksize: 0,
+            padding: 0,
+            flags: 0,
+        })
+    }
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                         Namespace                                          │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+#[derive(Clone, Copy)]
+pub struct Namespace<'a> {
+    pub projects: &'a Projects,
+    pub groups: &'a Groups,
+    pub mirrors: &'a Mirrors,
+    pub links: &'a Links,
+    pub crates: &'a Crates,
+    pub deps: &'a Deps,
+    pub git: &'a Git,
+    pub languages: &'a Languages,
+    pub recent: &'a Recent,
+    pub frequent: &'a Frequent,
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                           Index                                            │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+// Views that are scanned on access (git, recent, frequent, crates, deps) are listed as of their
+// last refresh. Views computed per lookup (at, search, worktrees, remotes, artifacts, info, usage,
+// providers) and the overlay aren't listed: they have no table to dump.
+#[derive(Serialize)]
+struct Index<'a> {
+    projects: BTreeMap<&'a str, &'a Path>,
+    aliases: BTreeMap<&'a str, &'a str>,
+    groups: BTreeMap<&'a str, Vec<&'a str>>,
+    mirrors: BTreeMap<&'a str, BTreeMap<String, &'a Path>>,
+    links: BTreeMap<&'a str, BTreeMap<&'a str, PathBuf>>,
+    crates: BTreeMap<&'a str, &'a Path>,
+    deps: BTreeMap<&'a str, BTreeMap<String, &'a Path>>,
+    git: BTreeMap<&'a str, Vec<&'a str>>,
+    languages: BTreeMap<&'a str, Vec<&'a str>>,
+    recent: Vec<&'a str>,
+    frequent: Vec<&'a str>,
+}
+
+impl<'a> Index<'a> {
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       new()                                        │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn new(namespace: Namespace<'a>) -> Self {
+        let Namespace { projects, groups, mirrors, .. } = namespace;
+
+        Index {
+            projects: projects.iter().collect(),
+            aliases: projects
+                .entries()
+                .filter_map(|(alias, _)| Some((alias, projects.name(alias)?)))
+                .filter(|(alias, name)| alias != name)
+                .collect(),
+            groups: groups
+                .iter()
+                .map(|(name, projects)| {
+                    let mut projects = projects.collect::<Vec<_>>();
+                    projects.sort_unstable();
+                    (name, projects)
+                })
+                .collect(),
+            mirrors: mirrors.index(),
+            links: namespace.links.index(projects),
+            crates: namespace.crates.index(),
+            deps: namespace.deps.index(),
+            git: namespace.git.index(),
+            languages: namespace.languages.index(),
+            recent: namespace.recent.index(),
+            frequent: namespace.frequent.index(),
+        }
+    }
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                           secs()                                           │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+fn secs(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs()
+}
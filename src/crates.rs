@@ -0,0 +1,344 @@
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                                                                            │ *
+ * │ This Source Code Form is subject to the terms of the Mozilla Public                        │ *
+ * │ License, v. 2.0. If a copy of the MPL was not distributed with this                        │ *
+ * │ file, You can obtain one at http://mozilla.org/MPL/2.0/.                                   │ *
+ * │                                                                                            │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                            use                                             │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+use super::{TTL, ROOT_INO};
+use super::projects::Projects;
+use fuser::{FileAttr, FileType, ReplyAttr, ReplyData, ReplyDirectory, ReplyEntry};
+use libc::ENOENT;
+use std::collections::{BTreeMap, HashMap};
+use std::ffi::{CString, OsStr};
+use std::fs;
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                           const                                            │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+pub const INO: u64 = 1 << 48;
+pub const STR: &str = "crates";
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                           Crates                                           │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+pub struct Crates {
+    ino: u64,
+    by_name: HashMap<String, u64>,
+    by_ino: HashMap<u64, CString>,
+    entries: BTreeMap<String, u64>,
+    workspaces: HashMap<String, Workspace>,
+}
+
+impl Crates {
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       new()                                        │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn new() -> Self {
+        Crates {
+            ino: INO,
+            by_name: HashMap::new(),
+            by_ino: HashMap::new(),
+            entries: BTreeMap::new(),
+            workspaces: HashMap::new(),
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     inherit()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn inherit(&mut self, old: &Self) {
+        self.ino = self.ino.max(old.ino);
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      index()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn index(&self) -> BTreeMap<&str, &Path> {
+        self.entries
+            .iter()
+            .filter_map(|(name, ino)| {
+                let path = self.by_ino.get(ino)?;
+                Some((name.as_str(), Path::new(OsStr::from_bytes(path.as_bytes()))))
+            })
+            .collect()
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     getattr()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn getattr(&self, ino: u64, reply: ReplyAttr) {
+        if let Some(attr) = self.attr(ino) {
+            reply.attr(&TTL, &attr);
+        } else {
+            reply.error(ENOENT);
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      lookup()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn lookup(&mut self, projects: &Projects, parent: u64, name: &str, reply: ReplyEntry) {
+        let ino = if parent == ROOT_INO {
+            INO
+        } else if parent == INO {
+            self.update(projects);
+
+            match self.entries.get(name) {
+                Some(ino) => *ino,
+                None => return reply.error(ENOENT),
+            }
+        } else {
+            return reply.error(ENOENT);
+        };
+
+        reply.entry(&TTL, &self.attr(ino).unwrap(), 0);
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     readdir()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn readdir(&mut self, projects: &Projects, ino: u64, offset: usize, mut reply: ReplyDirectory) {
+        if ino != INO {
+            return reply.error(ENOENT);
+        } else if offset == 0 {
+            self.update(projects);
+        }
+
+        for (idx, (name, ino)) in self.entries.iter().enumerate().skip(offset) {
+            if reply.add(*ino, (idx + 1) as i64, FileType::Symlink, name) {
+                break;
+            }
+        }
+
+        reply.ok();
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     readlink()                                     │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn readlink(&self, ino: u64, reply: ReplyData) {
+        match self.by_ino.get(&ino) {
+            Some(path) => reply.data(path.as_bytes()),
+            None => reply.error(ENOENT),
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      update()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn update(&mut self, projects: &Projects) {
+        let count = self.workspaces.len();
+        self.workspaces.retain(|name, _| projects.get(name).is_some());
+
+        let mut changed = self.workspaces.len() != count;
+        for (name, path) in projects.iter() {
+            let stale = match self.workspaces.get(name) {
+                Some(workspace) => workspace.is_stale(),
+                None => true,
+            };
+
+            if stale {
+                self.workspaces.insert(name.to_string(), Workspace::read(path));
+                changed = true;
+            }
+        }
+
+        if !changed {
+            return;
+        }
+
+        let mut crates = BTreeMap::<_, Vec<_>>::new();
+        for (project, workspace) in &self.workspaces {
+            for (name, path) in &workspace.crates {
+                crates.entry(name.clone()).or_default().push((project.clone(), path.clone()));
+            }
+        }
+
+        self.entries.clear();
+        for (name, paths) in crates {
+            let ambiguous = paths.len() > 1;
+            for (project, path) in paths {
+                let name = if ambiguous { format!("{}@{}", name, project) } else { name.clone() };
+                let ino = if let Some(ino) = self.by_name.get(&name) {
+                    *ino
+                } else {
+                    self.ino += 1;
+                    self.by_name.insert(name.clone(), self.ino);
+                    self.ino
+                };
+
+                self.by_ino.insert(ino, CString::new(path.as_os_str().as_bytes()).unwrap());
+                self.entries.insert(name, ino);
+            }
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       attr()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn attr(&self, ino: u64) -> Option<FileAttr> {
+        let (kind, nlink) = if ino == INO {
+            (FileType::Directory, 1 + self.entries.len() as u32)
+        } else if self.by_ino.contains_key(&ino) {
+            (FileType::Symlink, 1)
+        } else {
+            return None;
+        };
+
+        Some(FileAttr {
+            ino,
+            size: 0,
+            blocks: 0,
+            atime: UNIX_EPOCH,
+            mtime: UNIX_EPOCH,
+            ctime: UNIX_EPOCH,
+            crtime: UNIX_EPOCH,
+            kind,
+            perm: 0o555,
+            nlink,
+            uid: 0,
+            gid: 0,
+            rdev: 0,
+            blksize: 0,
+            padding: 0,
+            flags: 0,
+        })
+    }
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                         Workspace                                          │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+// Member globs are expanded again when checking for staleness, new member directories don't touch
+// any of the manifests.
+struct Workspace {
+    manifests: Vec<(PathBuf, Option<SystemTime>)>,
+    members: Vec<(String, Vec<PathBuf>)>,
+    crates: Vec<(String, PathBuf)>,
+}
+
+impl Workspace {
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       read()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn read(path: &Path) -> Self {
+        let mut workspace = Workspace {
+            manifests: Vec::new(),
+            members: Vec::new(),
+            crates: Vec::new(),
+        };
+
+        let root = match workspace.manifest(path) {
+            Some(root) => root,
+            None => return workspace,
+        };
+
+        let members = root
+            .get("workspace")
+            .and_then(|workspace| workspace.get("members"))
+            .and_then(toml::Value::as_array)
+            .cloned()
+            .unwrap_or_default();
+        let exclude = root
+            .get("workspace")
+            .and_then(|workspace| workspace.get("exclude"))
+            .and_then(toml::Value::as_array)
+            .map(|exclude| exclude.iter().filter_map(|dir| Some(path.join(dir.as_str()?))).collect())
+            .unwrap_or_else(Vec::new);
+
+        for member in members.iter().filter_map(toml::Value::as_str) {
+            let pattern = path.join(member).to_string_lossy().into_owned();
+            let dirs = match expand(&pattern) {
+                Ok(dirs) => dirs,
+                Err(err) => {
+                    log::warn!("invalid workspace member {} in {}: {}", member, path.display(), err);
+                    continue;
+                },
+            };
+
+            for dir in &dirs {
+                if dir != path && !exclude.contains(dir) {
+                    workspace.manifest(dir);
+                }
+            }
+
+            workspace.members.push((pattern, dirs));
+        }
+
+        workspace
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     manifest()                                     │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn manifest(&mut self, dir: &Path) -> Option<toml::Value> {
+        let path = dir.join("Cargo.toml");
+        let modified = fs::metadata(&path).and_then(|metadata| metadata.modified()).ok();
+        self.manifests.push((path.clone(), modified));
+        modified?;
+
+        let manifest = fs::read(&path).ok()?;
+        let manifest = match toml::from_slice::<toml::Value>(&manifest) {
+            Ok(manifest) => manifest,
+            Err(err) => {
+                log::warn!("failed to parse {}: {}", path.display(), err);
+                return None;
+            },
+        };
+
+        let name = manifest
+            .get("package")
+            .and_then(|package| package.get("name"))
+            .and_then(toml::Value::as_str);
+        if let Some(name) = name {
+            self.crates.push((name.to_string(), dir.to_path_buf()));
+        }
+
+        Some(manifest)
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     is_stale()                                     │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn is_stale(&self) -> bool {
+        let modified = self.manifests.iter().any(|(path, modified)| {
+            fs::metadata(path).and_then(|metadata| metadata.modified()).ok() != *modified
+        });
+
+        modified || self.members.iter().any(|(pattern, dirs)| match expand(pattern) {
+            Ok(expanded) => expanded != *dirs,
+            Err(_) => false,
+        })
+    }
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ * * │                                          expand()                                          │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+fn expand(pattern: &str) -> Result<Vec<PathBuf>, glob::PatternError> {
+    Ok(glob::glob(pattern)?.filter_map(Result::ok).collect())
+}
@@ -0,0 +1,468 @@
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                                                                            │ *
+ * │ This Source Code Form is subject to the terms of the Mozilla Public                        │ *
+ * │ License, v. 2.0. If a copy of the MPL was not distributed with this                        │ *
+ * │ file, You can obtain one at http://mozilla.org/MPL/2.0/.                                   │ *
+ * │                                                                                            │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                            use                                             │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+use super::{TTL, ROOT_INO};
+use fuser::{FileAttr, FileType, ReplyAttr, ReplyData, ReplyDirectory, ReplyEntry};
+use libc::ENOENT;
+use semver::Version;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                           const                                            │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+pub const INO: u64 = 1 << 46;
+pub const STR: &str = "deps";
+
+const LATEST: &str = "latest";
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                            Deps                                            │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+pub struct Deps {
+    ino: u64,
+    by_name: HashMap<(u64, String), u64>,
+    by_ino: HashMap<u64, Node>,
+    crates: BTreeMap<String, BTreeMap<Release, PathBuf>>,
+    dirs: Vec<(PathBuf, Option<SystemTime>)>,
+}
+
+impl Deps {
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       new()                                        │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn new() -> Self {
+        Deps {
+            ino: INO,
+            by_name: HashMap::new(),
+            by_ino: HashMap::new(),
+            crates: BTreeMap::new(),
+            dirs: Vec::new(),
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     inherit()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn inherit(&mut self, old: &Self) {
+        self.ino = self.ino.max(old.ino);
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      index()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn index(&self) -> BTreeMap<&str, BTreeMap<String, &Path>> {
+        self.crates
+            .iter()
+            .map(|(name, versions)| {
+                let versions = versions
+                    .iter()
+                    .map(|(Release(version), path)| (version.to_string(), path.as_path()))
+                    .collect();
+                (name.as_str(), versions)
+            })
+            .collect()
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     getattr()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn getattr(&self, ino: u64, reply: ReplyAttr) {
+        if let Some(attr) = self.attr(ino) {
+            reply.attr(&TTL, &attr);
+        } else {
+            reply.error(ENOENT);
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      lookup()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn lookup(&mut self, parent: u64, name: &str, reply: ReplyEntry) {
+        if parent == ROOT_INO {
+            return reply.entry(&TTL, &self.attr(INO).unwrap(), 0);
+        } else if parent == INO {
+            self.update();
+        }
+
+        match self.children(parent).into_iter().find(|(child, _)| child == name) {
+            Some((_, node)) => {
+                let ino = self.add_node(parent, name, node);
+                reply.entry(&TTL, &self.attr(ino).unwrap(), 0);
+            },
+            None => reply.error(ENOENT),
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     readdir()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn readdir(&mut self, ino: u64, offset: usize, mut reply: ReplyDirectory) {
+        if ino == INO && offset == 0 {
+            self.update();
+        } else if ino != INO && !matches!(self.by_ino.get(&ino), Some(Node::Crate(_))) {
+            return reply.error(ENOENT);
+        }
+
+        for (idx, (name, node)) in self.children(ino).into_iter().enumerate().skip(offset) {
+            let kind = node.kind();
+            let entry = self.add_node(ino, &name, node);
+            if reply.add(entry, (idx + 1) as i64, kind, name) {
+                break;
+            }
+        }
+
+        reply.ok();
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     readlink()                                     │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn readlink(&self, ino: u64, reply: ReplyData) {
+        match self.by_ino.get(&ino) {
+            Some(Node::Version(path)) => reply.data(path.as_os_str().as_bytes()),
+            _ => reply.error(ENOENT),
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     children()                                     │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn children(&self, ino: u64) -> Vec<(String, Node)> {
+        let versions = match self.by_ino.get(&ino) {
+            _ if ino == INO => {
+                return self.crates.keys().map(|name| (name.clone(), Node::Crate(name.clone()))).collect();
+            },
+            Some(Node::Crate(name)) => match self.crates.get(name) {
+                Some(versions) => versions,
+                None => return Vec::new(),
+            },
+            _ => return Vec::new(),
+        };
+
+        let mut children = versions
+            .iter()
+            .map(|(Release(version), path)| (version.to_string(), Node::Version(path.clone())))
+            .collect::<Vec<_>>();
+        if let Some((_, path)) = versions.iter().next_back() {
+            children.push((LATEST.into(), Node::Version(path.clone())));
+        }
+
+        children
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      update()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn update(&mut self) {
+        let stale = self.dirs.is_empty() || self.dirs.iter().any(|(path, modified)| {
+            fs::metadata(path).and_then(|metadata| metadata.modified()).ok() != *modified
+        });
+
+        if !stale {
+            return;
+        }
+
+        self.crates.clear();
+        self.dirs.clear();
+
+        let home = cargo_home();
+
+        // ~/.cargo/registry/src/<index>/<crate>-<version>
+        for (_, index) in self.track(&home.join("registry/src")) {
+            for (name, path) in self.track(&index) {
+                if let Some((name, version)) = split_version(&name) {
+                    self.add_crate(name, version, path);
+                }
+            }
+        }
+
+        // ~/.cargo/git/checkouts/<repo>-<hash>/<rev>/**/Cargo.toml
+        for (_, repo) in self.track(&home.join("git/checkouts")) {
+            for (rev, checkout) in self.track(&repo) {
+                for pattern in &["Cargo.toml", "*/Cargo.toml", "*/*/Cargo.toml"] {
+                    let manifests = match glob::glob(&checkout.join(pattern).to_string_lossy()) {
+                        Ok(manifests) => manifests.filter_map(Result::ok).collect::<Vec<_>>(),
+                        Err(_) => continue,
+                    };
+
+                    for manifest in manifests {
+                        if let Some((name, version)) = package(&manifest, &checkout, &rev) {
+                            self.add_crate(name, version, manifest.parent().unwrap().to_path_buf());
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      track()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn track(&mut self, dir: &Path) -> Vec<(String, PathBuf)> {
+        let modified = fs::metadata(dir).and_then(|metadata| metadata.modified()).ok();
+        self.dirs.push((dir.to_path_buf(), modified));
+
+        fs::read_dir(dir)
+            .into_iter()
+            .flatten()
+            .filter_map(Result::ok)
+            .filter(|entry| entry.file_type().map(|kind| kind.is_dir()).unwrap_or(false))
+            .filter_map(|entry| Some((entry.file_name().into_string().ok()?, entry.path())))
+            .collect()
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                    add_crate()                                     │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn add_crate(&mut self, name: String, version: Version, path: PathBuf) {
+        let versions = self.crates.entry(name).or_default();
+        let release = Release(version);
+        if let Some(other) = versions.get(&release) {
+            log::debug!("ignoring {}, already provided by {}", path.display(), other.display());
+        } else {
+            versions.insert(release, path);
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     add_node()                                     │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn add_node(&mut self, parent: u64, name: &str, node: Node) -> u64 {
+        let ino = if let Some(ino) = self.by_name.get(&(parent, name.to_string())) {
+            *ino
+        } else {
+            self.ino += 1;
+            self.by_name.insert((parent, name.to_string()), self.ino);
+            self.ino
+        };
+
+        self.by_ino.insert(ino, node);
+
+        ino
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       attr()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn attr(&self, ino: u64) -> Option<FileAttr> {
+        let kind = match self.by_ino.get(&ino) {
+            _ if ino == INO => FileType::Directory,
+            Some(node) => node.kind(),
+            None => return None,
+        };
+
+        Some(FileAttr {
+            ino,
+            size: 0,
+            blocks: 0,
+            atime: UNIX_EPOCH,
+            mtime: UNIX_EPOCH,
+            ctime: UNIX_EPOCH,
+            crtime: UNIX_EPOCH,
+            kind,
+            perm: 0o555,
+            nlink: if kind == FileType::Directory { 2 } else { 1 },
+            uid: 0,
+            gid: 0,
+            rdev: 0,
+            blksize: 0,
+            padding: 0,
+            flags: 0,
+        })
+    }
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                          Release                                           │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+// Versions are ordered by precedence. A registry release outranks git checkouts of the same
+// version, which are only ordered among themselves by revision.
+#[derive(PartialEq, Eq)]
+struct Release(Version);
+
+impl Ord for Release {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let key = |Release(version): &Release| {
+            let registry = version.build.is_empty();
+            (version.major, version.minor, version.patch, version.pre.clone(), registry)
+        };
+
+        key(self).cmp(&key(other)).then_with(|| self.0.build.cmp(&other.0.build))
+    }
+}
+
+impl PartialOrd for Release {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                            Node                                            │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+enum Node {
+    Crate(String),
+    Version(PathBuf),
+}
+
+impl Node {
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       kind()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn kind(&self) -> FileType {
+        match self {
+            Node::Crate(_) => FileType::Directory,
+            Node::Version(_) => FileType::Symlink,
+        }
+    }
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                        cargo_home()                                        │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+fn cargo_home() -> PathBuf {
+    if let Some(home) = std::env::var_os("CARGO_HOME") {
+        PathBuf::from(home)
+    } else if let Some(home) = std::env::var_os("HOME") {
+        PathBuf::from(home).join(".cargo")
+    } else {
+        PathBuf::from(".cargo")
+    }
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                      split_version()                                       │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+// Crate names may contain dashes as well as versions (`foo-bar-1.0.0-rc.1`), so the split is at
+// the first dash that is followed by a valid version.
+fn split_version(name: &str) -> Option<(String, Version)> {
+    name.match_indices('-')
+        .find_map(|(idx, _)| Some((name[..idx].to_string(), Version::parse(&name[idx + 1..]).ok()?)))
+        .filter(|(name, _)| !name.is_empty())
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                         package()                                          │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+// Git checkouts are versioned as `<version>+<rev>`, see `Release` for how they're ordered.
+fn package(manifest: &Path, checkout: &Path, rev: &str) -> Option<(String, Version)> {
+    let value = toml::from_slice::<toml::Value>(&fs::read(manifest).ok()?).ok()?;
+    let package = value.get("package")?;
+    let name = package.get("name")?.as_str()?;
+
+    // `version.workspace = true` comes from the closest workspace above, a missing version is
+    // 0.0.0 like Cargo does.
+    let inherited = toml::Value::Boolean(true);
+    let version = match package.get("version") {
+        Some(toml::Value::String(version)) => version.clone(),
+        Some(toml::Value::Table(table)) if table.get("workspace") == Some(&inherited) => {
+            workspace_version(manifest, checkout)?
+        },
+        Some(_) => return None,
+        None => "0.0.0".into(),
+    };
+
+    let mut version = Version::parse(&version).ok()?;
+    version.build = semver::BuildMetadata::new(rev).ok()?;
+
+    Some((name.to_string(), version))
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                    workspace_version()                                     │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+fn workspace_version(manifest: &Path, checkout: &Path) -> Option<String> {
+    manifest
+        .parent()?
+        .ancestors()
+        .take_while(|dir| dir.starts_with(checkout))
+        .find_map(|dir| {
+            let manifest = fs::read(dir.join("Cargo.toml")).ok()?;
+            let value = toml::from_slice::<toml::Value>(&manifest).ok()?;
+            let version = value.get("workspace")?.get("package")?.get("version")?.as_str()?;
+            Some(version.to_string())
+        })
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                           tests                                            │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+#[cfg(test)]
+mod tests {
+    use super::{split_version, Release};
+    use semver::Version;
+
+    #[test]
+    fn versions() {
+        let cases = [
+            ("serde-1.0.130", Some(("serde", "1.0.130"))),
+            ("foo-bar-1.0.0-rc.1", Some(("foo-bar", "1.0.0-rc.1"))),
+            ("foo-2-bar-0.1.0", Some(("foo-2-bar", "0.1.0"))),
+            ("foo-bar", None),
+            ("-1.0.0", None),
+        ];
+
+        for (name, expected) in cases.iter() {
+            let expected = expected.map(|(name, version)| {
+                (name.to_string(), Version::parse(version).unwrap())
+            });
+
+            assert_eq!(split_version(name), expected, "{}", name);
+        }
+    }
+
+    #[test]
+    fn releases() {
+        let release = |version| Release(Version::parse(version).unwrap());
+        let ordered = [
+            "0.9.0",
+            "1.0.0-rc.1+abc1234",
+            "1.0.0-rc.1",
+            "1.0.0+0123abc",
+            "1.0.0+abc1234",
+            "1.0.0",
+            "1.0.1+0123abc",
+        ];
+
+        for pair in ordered.windows(2) {
+            assert!(release(pair[0]) < release(pair[1]), "{} < {}", pair[0], pair[1]);
+        }
+    }
+}
@@ -0,0 +1,370 @@
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                                                                            │ *
+ * │ This Source Code Form is subject to the terms of the Mozilla Public                        │ *
+ * │ License, v. 2.0. If a copy of the MPL was not distributed with this                        │ *
+ * │ file, You can obtain one at http://mozilla.org/MPL/2.0/.                                   │ *
+ * │                                                                                            │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                            use                                             │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+use super::{TTL, ROOT_INO};
+use super::projects::Projects;
+use fuser::{FileAttr, FileType, ReplyAttr, ReplyData, ReplyDirectory, ReplyEntry, Request};
+use libc::ENOENT;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fs;
+use std::os::unix::ffi::OsStrExt;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                           const                                            │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+pub const INO: u64 = 1 << 58;
+pub const STR: &str = "frequent";
+
+const MAX_RANK: f64 = 10_000.0;
+const SAVE_INTERVAL: Duration = Duration::from_secs(30);
+const REPEAT_INTERVAL: u64 = 60;
+const LISTED_INTERVAL: Duration = Duration::from_secs(2);
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                          Frequent                                          │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+pub struct Frequent {
+    ino: u64,
+    count: usize,
+    ignore: Vec<String>,
+    path: PathBuf,
+    by_name: HashMap<String, u64>,
+    by_ino: HashMap<u64, String>,
+    entries: Vec<u64>,
+    listed: HashMap<u32, SystemTime>,
+    state: State,
+    saved: SystemTime,
+}
+
+impl Frequent {
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       new()                                        │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn new(path: PathBuf) -> Self {
+        let mut state: State = fs::read(&path)
+            .ok()
+            .and_then(|state| toml::from_slice(&state).ok())
+            .unwrap_or_default();
+
+        // TOML accepts `nan` and `inf`, which can't be ranked.
+        state.entries.retain(|_, entry| entry.rank.is_finite() && entry.rank >= 0.0);
+
+        Frequent {
+            ino: INO,
+            count: 10,
+            ignore: Vec::new(),
+            path,
+            by_name: HashMap::new(),
+            by_ino: HashMap::new(),
+            entries: Vec::new(),
+            listed: HashMap::new(),
+            state,
+            saved: SystemTime::now(),
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     inherit()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn inherit(&mut self, old: &Self) {
+        self.ino = self.ino.max(old.ino);
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                    configure()                                     │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn configure(&mut self, count: usize, ignore: Vec<String>) {
+        self.count = count;
+        self.ignore = ignore;
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      record()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    // A project is recorded when its target gets resolved, except by processes that just listed
+    // it (`ls -l` reads every link it lists). Repeated accesses in a short time count once.
+    pub fn record(&mut self, req: &Request, name: &str) {
+        let listed = self.listed.get(&req.pid()).and_then(|at| at.elapsed().ok());
+        if matches!(listed, Some(elapsed) if elapsed < LISTED_INTERVAL) {
+            return;
+        }
+
+        let now = now();
+        if let Some(entry) = self.state.entries.get(name) {
+            if now.saturating_sub(entry.last) < REPEAT_INTERVAL {
+                return;
+            }
+        }
+
+        let comm = fs::read_to_string(format!("/proc/{}/comm", req.pid())).unwrap_or_default();
+        if self.ignore.iter().any(|ignore| ignore == comm.trim_end()) {
+            return;
+        }
+
+        let entry = self.state.entries.entry(name.to_string()).or_insert(Entry { rank: 0.0, last: now });
+        entry.rank += 1.0;
+        entry.last = now;
+
+        let total = self.state.entries.values().map(|entry| entry.rank).sum::<f64>();
+        if total > MAX_RANK {
+            let factor = 0.9 * MAX_RANK / total;
+            self.state.entries.retain(|_, entry| {
+                entry.rank *= factor;
+                entry.rank >= 1.0
+            });
+        }
+
+        if self.saved.elapsed().unwrap_or_default() > SAVE_INTERVAL {
+            self.save();
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      listed()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn listed(&mut self, req: &Request) {
+        self.listed.retain(|_, at| at.elapsed().unwrap_or_default() < LISTED_INTERVAL);
+        self.listed.insert(req.pid(), SystemTime::now());
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       save()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    // The state is written next to the file and renamed over it, so that a crash while saving
+    // doesn't leave a truncated file behind.
+    pub fn save(&mut self) {
+        self.saved = SystemTime::now();
+
+        if let Some(parent) = self.path.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+
+        let state = match toml::to_vec(&self.state) {
+            Ok(state) => state,
+            Err(err) => return log::warn!("failed to serialize frecency state: {}", err),
+        };
+
+        let tmp = self.path.with_extension("toml.tmp");
+        if let Err(err) = fs::write(&tmp, state).and_then(|_| fs::rename(&tmp, &self.path)) {
+            log::warn!("failed to save {}: {}", self.path.display(), err);
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      index()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn index(&self) -> Vec<&str> {
+        self.entries.iter().filter_map(|ino| Some(self.by_ino.get(ino)?.as_str())).collect()
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     getattr()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn getattr(&self, ino: u64, reply: ReplyAttr) {
+        if let Some(attr) = self.attr(ino) {
+            reply.attr(&TTL, &attr);
+        } else {
+            reply.error(ENOENT);
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      lookup()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn lookup(&mut self, projects: &Projects, parent: u64, name: &str, reply: ReplyEntry) {
+        let ino = if parent == ROOT_INO {
+            INO
+        } else if parent == INO {
+            self.update(projects);
+
+            match self.by_name.get(name) {
+                Some(ino) if self.entries.contains(ino) => *ino,
+                _ => return reply.error(ENOENT),
+            }
+        } else {
+            return reply.error(ENOENT);
+        };
+
+        reply.entry(&TTL, &self.attr(ino).unwrap(), 0);
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     readdir()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn readdir(&mut self, projects: &Projects, ino: u64, offset: usize, mut reply: ReplyDirectory) {
+        if ino != INO {
+            return reply.error(ENOENT);
+        } else if offset == 0 {
+            self.update(projects);
+        }
+
+        for (idx, ino) in self.entries.iter().enumerate().skip(offset) {
+            if reply.add(*ino, (idx + 1) as i64, FileType::Symlink, &self.by_ino[ino]) {
+                break;
+            }
+        }
+
+        reply.ok();
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     readlink()                                     │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn readlink(&self, projects: &Projects, ino: u64, reply: ReplyData) {
+        match self.by_ino.get(&ino).and_then(|name| projects.get(name)) {
+            Some(path) => reply.data(path.as_os_str().as_bytes()),
+            None => reply.error(ENOENT),
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      update()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn update(&mut self, projects: &Projects) {
+        let now = now();
+
+        let mut scores = self.state.entries
+            .iter()
+            .filter(|(name, _)| projects.get(name).is_some())
+            .map(|(name, entry)| (name.clone(), entry.score(now)))
+            .collect::<Vec<_>>();
+        scores.sort_by(|(_, a), (_, b)| b.partial_cmp(a).unwrap_or(Ordering::Equal));
+
+        self.entries.clear();
+        for (name, _) in scores.into_iter().take(self.count) {
+            let ino = if let Some(ino) = self.by_name.get(&name) {
+                *ino
+            } else {
+                self.ino += 1;
+                self.by_name.insert(name.clone(), self.ino);
+                self.by_ino.insert(self.ino, name);
+                self.ino
+            };
+
+            self.entries.push(ino);
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       attr()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn attr(&self, ino: u64) -> Option<FileAttr> {
+        if ino == INO {
+            Some(FileAttr {
+                ino,
+                size: 0,
+                blocks: 0,
+                atime: UNIX_EPOCH,
+                mtime: UNIX_EPOCH,
+                ctime: UNIX_EPOCH,
+                crtime: UNIX_EPOCH,
+                kind: FileType::Directory,
+                perm: 0o555,
+                nlink: 1 + self.entries.len() as u32,
+                uid: 0,
+                gid: 0,
+                rdev: 0,
+                blksize: 0,
+                padding: 0,
+                flags: 0,
+            })
+        } else {
+            let entry = self.state.entries.get(self.by_ino.get(&ino)?)?;
+            let last = UNIX_EPOCH + Duration::from_secs(entry.last);
+
+            Some(FileAttr {
+                ino,
+                size: 0,
+                blocks: 0,
+                atime: last,
+                mtime: last,
+                ctime: last,
+                crtime: UNIX_EPOCH,
+                kind: FileType::Symlink,
+                perm: 0o555,
+                nlink: 1,
+                uid: 0,
+                gid: 0,
+                rdev: 0,
+                blksize: 0,
+                padding: 0,
+                flags: 0,
+            })
+        }
+    }
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                           State                                            │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+#[derive(Default, Deserialize, Serialize)]
+struct State {
+    entries: HashMap<String, Entry>,
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                           Entry                                            │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+#[derive(Deserialize, Serialize)]
+struct Entry {
+    rank: f64,
+    last: u64,
+}
+
+impl Entry {
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      score()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn score(&self, now: u64) -> f64 {
+        let age = now.saturating_sub(self.last);
+
+        if age < 60 * 60 {
+            self.rank * 4.0
+        } else if age < 24 * 60 * 60 {
+            self.rank * 2.0
+        } else if age < 7 * 24 * 60 * 60 {
+            self.rank * 0.5
+        } else {
+            self.rank * 0.25
+        }
+    }
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                           now()                                            │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs()
+}
@@ -0,0 +1,508 @@
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                                                                            │ *
+ * │ This Source Code Form is subject to the terms of the Mozilla Public                        │ *
+ * │ License, v. 2.0. If a copy of the MPL was not distributed with this                        │ *
+ * │ file, You can obtain one at http://mozilla.org/MPL/2.0/.                                   │ *
+ * │                                                                                            │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                            use                                             │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+use super::{TTL, ROOT_INO};
+use super::projects::Projects;
+use fuser::{FileAttr, FileType, ReplyAttr, ReplyData, ReplyDirectory, ReplyEntry};
+use git2::{Branch, Repository, StatusOptions};
+use libc::ENOENT;
+use std::collections::{BTreeMap, HashMap};
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, TryRecvError};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                           const                                            │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+pub const INO: u64 = 1 << 57;
+pub const STR: &str = "git";
+
+const VIEWS: [&str; 4] = ["dirty", "ahead", "behind", "detached"];
+
+// How long the very first listing waits for the statuses before showing nothing.
+const WAIT: Duration = Duration::from_millis(500);
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                            Git                                             │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+pub struct Git {
+    ino: u64,
+    interval: Duration,
+    by_name: HashMap<(u64, String), u64>,
+    by_ino: HashMap<u64, (u64, String)>,
+    statuses: HashMap<String, Status>,
+    pending: Option<Receiver<HashMap<String, Status>>>,
+    checked: SystemTime,
+    updated: SystemTime,
+}
+
+impl Git {
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       new()                                        │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn new() -> Self {
+        Git {
+            ino: INO + VIEWS.len() as u64,
+            interval: Duration::from_secs(30),
+            by_name: HashMap::new(),
+            by_ino: HashMap::new(),
+            statuses: HashMap::new(),
+            pending: None,
+            checked: UNIX_EPOCH,
+            updated: UNIX_EPOCH,
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     inherit()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn inherit(&mut self, old: &mut Self) {
+        // The previous statuses are listed until the new ones are in, like between two scans.
+        self.ino = self.ino.max(old.ino);
+        self.statuses = std::mem::take(&mut old.statuses);
+        self.updated = old.updated;
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                    configure()                                     │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn configure(&mut self, interval: Duration) {
+        self.interval = interval;
+        self.checked = UNIX_EPOCH;
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      index()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn index(&self) -> BTreeMap<&str, Vec<&str>> {
+        (1..=VIEWS.len() as u64)
+            .map(|idx| {
+                let mut names = self.statuses
+                    .iter()
+                    .filter(|(_, status)| status.matches(INO + idx))
+                    .map(|(name, _)| name.as_str())
+                    .collect::<Vec<_>>();
+                names.sort_unstable();
+                (VIEWS[idx as usize - 1], names)
+            })
+            .collect()
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     getattr()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn getattr(&self, ino: u64, reply: ReplyAttr) {
+        if let Some(attr) = self.attr(ino) {
+            reply.attr(&TTL, &attr);
+        } else {
+            reply.error(ENOENT);
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      lookup()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn lookup(&mut self, projects: &Projects, parent: u64, name: &str, reply: ReplyEntry) {
+        let ino = if parent == ROOT_INO {
+            INO
+        } else if parent == INO {
+            match VIEWS.iter().position(|view| *view == name) {
+                Some(idx) => INO + 1 + idx as u64,
+                None => return reply.error(ENOENT),
+            }
+        } else if is_view(parent) {
+            self.update(projects);
+
+            match self.statuses.get(name) {
+                Some(status) if status.matches(parent) => self.add_entry(parent, name),
+                _ => return reply.error(ENOENT),
+            }
+        } else {
+            return reply.error(ENOENT);
+        };
+
+        reply.entry(&TTL, &self.attr(ino).unwrap(), 0);
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     readdir()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn readdir(&mut self, projects: &Projects, ino: u64, offset: usize, mut reply: ReplyDirectory) {
+        if ino == INO {
+            for (idx, view) in VIEWS.iter().enumerate().skip(offset) {
+                if reply.add(INO + 1 + idx as u64, (idx + 1) as i64, FileType::Directory, view) {
+                    break;
+                }
+            }
+        } else if is_view(ino) {
+            if offset == 0 {
+                self.update(projects);
+            }
+
+            let mut names = self.statuses
+                .iter()
+                .filter(|(_, status)| status.matches(ino))
+                .map(|(name, _)| name.clone())
+                .collect::<Vec<_>>();
+            names.sort_unstable();
+
+            for (idx, name) in names.into_iter().enumerate().skip(offset) {
+                let entry = self.add_entry(ino, &name);
+                if reply.add(entry, (idx + 1) as i64, FileType::Symlink, name) {
+                    break;
+                }
+            }
+        } else {
+            return reply.error(ENOENT);
+        }
+
+        reply.ok();
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     readlink()                                     │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn readlink(&self, projects: &Projects, ino: u64, reply: ReplyData) {
+        match self.by_ino.get(&ino).and_then(|(_, name)| projects.get(name)) {
+            Some(path) => reply.data(path.as_os_str().as_bytes()),
+            None => reply.error(ENOENT),
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      update()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    // Statuses walk every working tree, untracked files included, so they're read on their own
+    // thread. Only the first listing waits for them (for at most `WAIT`), later ones get the
+    // previous statuses until the new ones are in.
+    fn update(&mut self, projects: &Projects) {
+        let expired = self.checked.elapsed().unwrap_or_default() >= self.interval;
+        if self.pending.is_none() && expired {
+            let projects = projects
+                .iter()
+                .map(|(name, path)| (name.to_string(), path.to_path_buf()))
+                .collect::<Vec<(String, PathBuf)>>();
+
+            let (sender, receiver) = mpsc::channel();
+            thread::spawn(move || {
+                let statuses = projects
+                    .into_iter()
+                    .filter_map(|(name, path)| Some((name, Status::read(&path)?)))
+                    .collect();
+                let _ = sender.send(statuses);
+            });
+
+            self.checked = SystemTime::now();
+            self.pending = Some(receiver);
+        }
+
+        let statuses = match &self.pending {
+            Some(pending) if self.updated == UNIX_EPOCH => {
+                pending.recv_timeout(WAIT).map_err(|err| match err {
+                    RecvTimeoutError::Timeout => TryRecvError::Empty,
+                    RecvTimeoutError::Disconnected => TryRecvError::Disconnected,
+                })
+            },
+            Some(pending) => pending.try_recv(),
+            None => return,
+        };
+
+        match statuses {
+            Ok(statuses) => {
+                self.statuses = statuses;
+                self.updated = SystemTime::now();
+                self.pending = None;
+            },
+            Err(TryRecvError::Empty) => (),
+            Err(TryRecvError::Disconnected) => self.pending = None,
+        }
+
+        // Inherited statuses can belong to projects that are gone since.
+        self.statuses.retain(|name, _| projects.get(name).is_some());
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                    add_entry()                                     │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn add_entry(&mut self, view: u64, name: &str) -> u64 {
+        if let Some(ino) = self.by_name.get(&(view, name.to_string())) {
+            return *ino;
+        }
+
+        self.ino += 1;
+
+        self.by_name.insert((view, name.to_string()), self.ino);
+        self.by_ino.insert(self.ino, (view, name.to_string()));
+
+        self.ino
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       attr()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn attr(&self, ino: u64) -> Option<FileAttr> {
+        if ino == INO || is_view(ino) {
+            Some(FileAttr {
+                ino,
+                size: 0,
+                blocks: 0,
+                atime: UNIX_EPOCH,
+                mtime: self.updated,
+                ctime: self.updated,
+                crtime: UNIX_EPOCH,
+                kind: FileType::Directory,
+                perm: 0o555,
+                nlink: 2,
+                uid: 0,
+                gid: 0,
+                rdev: 0,
+                blksize: 0,
+                padding: 0,
+                flags: 0,
+            })
+        } else if self.by_ino.contains_key(&ino) {
+            Some(FileAttr {
+                ino,
+                size: 0,
+                blocks: 0,
+                atime: UNIX_EPOCH,
+                mtime: UNIX_EPOCH,
+                ctime: UNIX_EPOCH,
+                crtime: UNIX_EPOCH,
+                kind: FileType::Symlink,
+                perm: 0o555,
+                nlink: 1,
+                uid: 0,
+                gid: 0,
+                rdev: 0,
+                blksize: 0,
+                padding: 0,
+                flags: 0,
+            })
+        } else {
+            None
+        }
+    }
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                           Status                                           │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+struct Status {
+    dirty: bool,
+    ahead: usize,
+    behind: usize,
+    detached: bool,
+}
+
+impl Status {
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       read()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn read(path: &Path) -> Option<Self> {
+        let repo = Repository::open(path).ok()?;
+
+        let mut options = StatusOptions::new();
+        options
+            .include_untracked(true)
+            .include_ignored(false)
+            .exclude_submodules(true);
+
+        let dirty = repo
+            .statuses(Some(&mut options))
+            .map(|statuses| !statuses.is_empty())
+            .unwrap_or(false);
+        let (ahead, behind) = upstream(&repo).unwrap_or((0, 0));
+
+        Some(Status {
+            dirty,
+            ahead,
+            behind,
+            detached: repo.head_detached().unwrap_or(false),
+        })
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     matches()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn matches(&self, view: u64) -> bool {
+        match VIEWS[(view - INO - 1) as usize] {
+            "dirty" => self.dirty,
+            "ahead" => self.ahead > 0,
+            "behind" => self.behind > 0,
+            "detached" => self.detached,
+            _ => false,
+        }
+    }
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                         upstream()                                         │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+fn upstream(repo: &Repository) -> Option<(usize, usize)> {
+    let head = repo.head().ok()?;
+    if !head.is_branch() {
+        return None;
+    }
+
+    let branch = Branch::wrap(head);
+    let local = branch.get().target()?;
+    let upstream = branch.upstream().ok()?.get().target()?;
+
+    repo.graph_ahead_behind(local, upstream).ok()
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                         is_view()                                          │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+fn is_view(ino: u64) -> bool {
+    ino > INO && ino <= INO + VIEWS.len() as u64
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                           tests                                            │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+#[cfg(test)]
+mod tests {
+    use super::Status;
+    use git2::{Oid, Repository, Signature};
+    use std::fs;
+    use std::path::PathBuf;
+
+    fn repo(name: &str) -> (PathBuf, Repository) {
+        let name = format!("pr0j3c75-git-{}-{}", std::process::id(), name);
+        let path = std::env::temp_dir().join(name);
+        let _ = fs::remove_dir_all(&path);
+        let repo = Repository::init(&path).unwrap();
+        repo.remote("origin", "/nonexistent").unwrap();
+
+        (path, repo)
+    }
+
+    fn commit(repo: &Repository, file: &str) -> Oid {
+        fs::write(repo.workdir().unwrap().join(file), file).unwrap();
+
+        let mut index = repo.index().unwrap();
+        index.add_path(file.as_ref()).unwrap();
+        index.write().unwrap();
+
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = Signature::now("pr0j3c75", "pr0j3c75@localhost").unwrap();
+        let parent = repo.head().ok().and_then(|head| head.peel_to_commit().ok());
+        let parents = parent.iter().collect::<Vec<_>>();
+
+        repo.commit(Some("HEAD"), &signature, &signature, file, &tree, &parents).unwrap()
+    }
+
+    // Points `origin/<branch>` at `oid` and makes it the upstream of the current branch.
+    fn track(repo: &Repository, oid: Oid) {
+        let head = repo.head().unwrap();
+        let branch = head.shorthand().unwrap().to_string();
+        let upstream = format!("refs/remotes/origin/{}", branch);
+        repo.reference(&upstream, oid, true, "").unwrap();
+
+        let mut config = repo.config().unwrap();
+        config.set_str(&format!("branch.{}.remote", branch), "origin").unwrap();
+        let merge = format!("refs/heads/{}", branch);
+        config.set_str(&format!("branch.{}.merge", branch), &merge).unwrap();
+    }
+
+    fn read(name: &str, setup: impl FnOnce(&Repository)) -> Status {
+        let (path, repo) = repo(name);
+        setup(&repo);
+        let status = Status::read(&path).unwrap();
+        let _ = fs::remove_dir_all(&path);
+
+        status
+    }
+
+    #[test]
+    fn clean() {
+        let status = read("clean", |repo| {
+            let oid = commit(repo, "a");
+            track(repo, oid);
+        });
+
+        assert!(!status.dirty && !status.detached);
+        assert_eq!((status.ahead, status.behind), (0, 0));
+    }
+
+    #[test]
+    fn dirty() {
+        let status = read("dirty", |repo| {
+            commit(repo, "a");
+            fs::write(repo.workdir().unwrap().join("b"), "b").unwrap();
+        });
+
+        assert!(status.dirty);
+    }
+
+    #[test]
+    fn ahead() {
+        let status = read("ahead", |repo| {
+            let oid = commit(repo, "a");
+            track(repo, oid);
+            commit(repo, "b");
+            commit(repo, "c");
+        });
+
+        assert!(!status.dirty);
+        assert_eq!((status.ahead, status.behind), (2, 0));
+    }
+
+    #[test]
+    fn behind() {
+        let status = read("behind", |repo| {
+            let first = commit(repo, "a");
+            let second = commit(repo, "b");
+            track(repo, second);
+
+            let commit = repo.find_commit(first).unwrap();
+            repo.reset(commit.as_object(), git2::ResetType::Hard, None).unwrap();
+        });
+
+        assert!(!status.dirty);
+        assert_eq!((status.ahead, status.behind), (0, 1));
+    }
+
+    #[test]
+    fn detached() {
+        let status = read("detached", |repo| {
+            let oid = commit(repo, "a");
+            repo.set_head_detached(oid).unwrap();
+        });
+
+        assert!(status.detached);
+        assert_eq!((status.ahead, status.behind), (0, 0));
+    }
+}
@@ -46,6 +46,14 @@ impl Groups {
         }
     }
 
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     inherit()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn inherit(&mut self, old: &Self) {
+        self.ino = self.ino.max(old.ino);
+    }
+
 /*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
  *     │                                    add_group()                                     │     *
 \*     └────────────────────────────────────────────────────────────────────────────────────┘     */
@@ -69,6 +77,17 @@ impl Groups {
         self.by_ino.get_mut(&ino)
     }
 
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       iter()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, impl Iterator<Item = &str>)> {
+        self.by_name.iter().filter_map(move |(name, ino)| {
+            let group = self.by_ino.get(ino)?;
+            Some((name.as_str(), group.by_ino.values().map(String::as_str)))
+        })
+    }
+
 /*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
  *     │                                     getattr()                                      │     *
 \*     └────────────────────────────────────────────────────────────────────────────────────┘     */
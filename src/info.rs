@@ -0,0 +1,281 @@
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                                                                            │ *
+ * │ This Source Code Form is subject to the terms of the Mozilla Public                        │ *
+ * │ License, v. 2.0. If a copy of the MPL was not distributed with this                        │ *
+ * │ file, You can obtain one at http://mozilla.org/MPL/2.0/.                                   │ *
+ * │                                                                                            │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                            use                                             │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+use super::{TTL, ROOT_INO};
+use fuser::{FileAttr, FileType, ReplyAttr, ReplyData, ReplyDirectory, ReplyEntry};
+use git2::Repository;
+use libc::ENOENT;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::time::UNIX_EPOCH;
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                           const                                            │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+pub const INO: u64 = 1 << 51;
+pub const STR: &str = "info";
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                            Info                                            │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+pub struct Info {
+    ino: u64,
+    by_name: BTreeMap<String, u64>,
+    by_ino: HashMap<u64, (Format, usize)>,
+    projects: Vec<Metadata>,
+}
+
+impl Info {
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       new()                                        │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn new() -> Self {
+        Info {
+            ino: INO,
+            by_name: BTreeMap::new(),
+            by_ino: HashMap::new(),
+            projects: Vec::new(),
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     inherit()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn inherit(&mut self, old: &Self) {
+        self.ino = self.ino.max(old.ino);
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                   add_project()                                    │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn add_project(
+        &mut self,
+        name: String,
+        path: String,
+        aliases: Vec<String>,
+        groups: Vec<String>,
+        tags: Vec<String>,
+    ) {
+        let idx = self.projects.len();
+
+        for format in &[Format::Toml, Format::Json] {
+            self.ino += 1;
+            self.by_name.insert(format!("{}.{}", name, format.extension()), self.ino);
+            self.by_ino.insert(self.ino, (*format, idx));
+        }
+
+        self.projects.push(Metadata {
+            name,
+            path,
+            aliases,
+            groups,
+            tags,
+            git: None,
+        });
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     getattr()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn getattr(&self, ino: u64, reply: ReplyAttr) {
+        if let Some(attr) = self.attr(ino) {
+            reply.attr(&TTL, &attr);
+        } else {
+            reply.error(ENOENT);
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      lookup()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn lookup(&self, parent: u64, name: &str, reply: ReplyEntry) {
+        let ino = if parent == ROOT_INO {
+            Some(INO)
+        } else if parent == INO {
+            self.by_name.get(name).copied()
+        } else {
+            None
+        };
+
+        match ino.and_then(|ino| self.attr(ino)) {
+            Some(attr) => reply.entry(&TTL, &attr, 0),
+            None => reply.error(ENOENT),
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     readdir()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn readdir(&self, ino: u64, offset: usize, mut reply: ReplyDirectory) {
+        if ino != INO {
+            return reply.error(ENOENT);
+        }
+
+        for (idx, (name, ino)) in self.by_name.iter().enumerate().skip(offset) {
+            if reply.add(*ino, (idx + 1) as i64, FileType::RegularFile, name) {
+                break;
+            }
+        }
+
+        reply.ok();
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       read()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn read(&self, ino: u64, offset: i64, size: u32, reply: ReplyData) {
+        match self.render(ino) {
+            Some(data) => {
+                let start = (offset.max(0) as usize).min(data.len());
+                let end = (start + size as usize).min(data.len());
+                reply.data(&data[start..end]);
+            },
+            None => reply.error(ENOENT),
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      render()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn render(&self, ino: u64) -> Option<Vec<u8>> {
+        let (format, idx) = self.by_ino.get(&ino)?;
+        let project = &self.projects[*idx];
+        let metadata = Metadata {
+            git: Git::read(&project.path),
+            ..project.clone()
+        };
+
+        let data = match format {
+            Format::Toml => toml::to_vec(&metadata).ok()?,
+            Format::Json => {
+                let mut data = serde_json::to_vec_pretty(&metadata).ok()?;
+                data.push(b'\n');
+                data
+            },
+        };
+
+        Some(data)
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       attr()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn attr(&self, ino: u64) -> Option<FileAttr> {
+        let (kind, size, perm, nlink) = if ino == INO {
+            (FileType::Directory, 0, 0o555, 2)
+        } else {
+            (FileType::RegularFile, self.render(ino)?.len() as u64, 0o444, 1)
+        };
+
+        Some(FileAttr {
+            ino,
+            size,
+            blocks: 0,
+            atime: UNIX_EPOCH,
+            mtime: UNIX_EPOCH,
+            ctime: UNIX_EPOCH,
+            crtime: UNIX_EPOCH,
+            kind,
+            perm,
+            nlink,
+            uid: 0,
+            gid: 0,
+            rdev: 0,
+            blksize: 0,
+            padding: 0,
+            flags: 0,
+        })
+    }
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                           Format                                           │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+#[derive(Clone, Copy)]
+enum Format {
+    Toml,
+    Json,
+}
+
+impl Format {
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                    extension()                                     │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn extension(self) -> &'static str {
+        match self {
+            Format::Toml => "toml",
+            Format::Json => "json",
+        }
+    }
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                          Metadata                                          │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+#[derive(Clone, Serialize)]
+struct Metadata {
+    name: String,
+    path: String,
+    aliases: Vec<String>,
+    groups: Vec<String>,
+    tags: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    git: Option<Git>,
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                            Git                                             │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+#[derive(Clone, Serialize)]
+struct Git {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    branch: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    remote: Option<String>,
+}
+
+impl Git {
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       read()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn read(path: &str) -> Option<Self> {
+        let repo = Repository::open(path).ok()?;
+
+        let branch = repo
+            .head()
+            .ok()
+            .filter(|head| head.is_branch())
+            .and_then(|head| head.shorthand().map(str::to_string));
+        let remote = repo
+            .find_remote("origin")
+            .ok()
+            .and_then(|remote| remote.url().map(str::to_string));
+
+        Some(Git { branch, remote })
+    }
+}
@@ -0,0 +1,262 @@
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                                                                            │ *
+ * │ This Source Code Form is subject to the terms of the Mozilla Public                        │ *
+ * │ License, v. 2.0. If a copy of the MPL was not distributed with this                        │ *
+ * │ file, You can obtain one at http://mozilla.org/MPL/2.0/.                                   │ *
+ * │                                                                                            │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                            use                                             │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+use super::{TTL, ROOT_INO};
+use super::projects::Projects;
+use fuser::{FileAttr, FileType, ReplyAttr, ReplyData, ReplyDirectory, ReplyEntry};
+use libc::ENOENT;
+use std::collections::{BTreeMap, HashMap};
+use std::ffi::CString;
+use std::os::unix::ffi::OsStrExt;
+use std::time::UNIX_EPOCH;
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                           const                                            │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+pub const INO: u64 = 1 << 55;
+pub const STR: &str = "lang";
+
+const MARKERS: [(&str, &str); 12] = [
+    ("Cargo.toml", "rust"),
+    ("package.json", "javascript"),
+    ("deno.json", "javascript"),
+    ("tsconfig.json", "typescript"),
+    ("go.mod", "go"),
+    ("pyproject.toml", "python"),
+    ("setup.py", "python"),
+    ("flake.nix", "nix"),
+    ("default.nix", "nix"),
+    ("mix.exs", "elixir"),
+    ("Gemfile", "ruby"),
+    ("pom.xml", "java"),
+];
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                         Languages                                          │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+pub struct Languages {
+    ino: u64,
+    markers: HashMap<String, String>,
+    languages: BTreeMap<String, u64>,
+    projects: HashMap<u64, BTreeMap<String, u64>>,
+    by_name: HashMap<(u64, String), u64>,
+    by_ino: HashMap<u64, Node>,
+}
+
+impl Languages {
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       new()                                        │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn new() -> Self {
+        Languages {
+            ino: INO,
+            markers: MARKERS
+                .iter()
+                .map(|(marker, language)| (marker.to_string(), language.to_string()))
+                .collect(),
+            languages: BTreeMap::new(),
+            projects: HashMap::new(),
+            by_name: HashMap::new(),
+            by_ino: HashMap::new(),
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     inherit()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn inherit(&mut self, old: &Self) {
+        self.ino = self.ino.max(old.ino);
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                    configure()                                     │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn configure(&mut self, markers: HashMap<String, String>) {
+        for (marker, language) in markers {
+            if language.is_empty() {
+                self.markers.remove(&marker);
+            } else {
+                self.markers.insert(marker, language);
+            }
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     classify()                                     │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn classify(&mut self, projects: &Projects) {
+        self.languages.clear();
+        self.projects.clear();
+
+        for (name, path) in projects.iter() {
+            let languages = self.markers
+                .iter()
+                .filter(|(marker, _)| path.join(marker).exists())
+                .map(|(_, language)| language.clone())
+                .collect::<Vec<_>>();
+
+            for language in languages {
+                let path = CString::new(path.as_os_str().as_bytes()).unwrap();
+                let parent = self.add_node(INO, &language, Node::Language);
+                let ino = self.add_node(parent, name, Node::Project(path));
+
+                self.languages.insert(language, parent);
+                self.projects.entry(parent).or_default().insert(name.to_string(), ino);
+            }
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      index()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn index(&self) -> BTreeMap<&str, Vec<&str>> {
+        self.languages
+            .iter()
+            .map(|(language, ino)| {
+                let projects = self.projects.get(ino).into_iter().flat_map(BTreeMap::keys);
+                (language.as_str(), projects.map(String::as_str).collect())
+            })
+            .collect()
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     getattr()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn getattr(&self, ino: u64, reply: ReplyAttr) {
+        if let Some(attr) = self.attr(ino) {
+            reply.attr(&TTL, &attr);
+        } else {
+            reply.error(ENOENT);
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      lookup()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn lookup(&self, parent: u64, name: &str, reply: ReplyEntry) {
+        let ino = if parent == ROOT_INO {
+            Some(INO)
+        } else if parent == INO {
+            self.languages.get(name).copied()
+        } else {
+            self.projects.get(&parent).and_then(|projects| projects.get(name).copied())
+        };
+
+        match ino.and_then(|ino| self.attr(ino)) {
+            Some(attr) => reply.entry(&TTL, &attr, 0),
+            None => reply.error(ENOENT),
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     readdir()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn readdir(&self, ino: u64, offset: usize, mut reply: ReplyDirectory) {
+        if ino == INO {
+            for (idx, (language, ino)) in self.languages.iter().enumerate().skip(offset) {
+                if reply.add(*ino, (idx + 1) as i64, FileType::Directory, language) {
+                    break;
+                }
+            }
+        } else if let Some(projects) = self.projects.get(&ino) {
+            for (idx, (name, ino)) in projects.iter().enumerate().skip(offset) {
+                if reply.add(*ino, (idx + 1) as i64, FileType::Symlink, name) {
+                    break;
+                }
+            }
+        } else {
+            return reply.error(ENOENT);
+        }
+
+        reply.ok();
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     readlink()                                     │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn readlink(&self, ino: u64, reply: ReplyData) {
+        match self.by_ino.get(&ino) {
+            Some(Node::Project(path)) => reply.data(path.as_bytes()),
+            _ => reply.error(ENOENT),
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     add_node()                                     │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn add_node(&mut self, parent: u64, name: &str, node: Node) -> u64 {
+        let ino = if let Some(ino) = self.by_name.get(&(parent, name.to_string())) {
+            *ino
+        } else {
+            self.ino += 1;
+            self.by_name.insert((parent, name.to_string()), self.ino);
+            self.ino
+        };
+
+        self.by_ino.insert(ino, node);
+
+        ino
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       attr()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn attr(&self, ino: u64) -> Option<FileAttr> {
+        let (kind, nlink) = match self.by_ino.get(&ino) {
+            _ if ino == INO => (FileType::Directory, 1 + self.languages.len() as u32),
+            Some(Node::Language) => (FileType::Directory, 1 + self.projects.get(&ino)?.len() as u32),
+            Some(Node::Project(_)) => (FileType::Symlink, 1),
+            None => return None,
+        };
+
+        Some(FileAttr {
+            ino,
+            size: 0,
+            blocks: 0,
+            atime: UNIX_EPOCH,
+            mtime: UNIX_EPOCH,
+            ctime: UNIX_EPOCH,
+            crtime: UNIX_EPOCH,
+            kind,
+            perm: 0o555,
+            nlink,
+            uid: 0,
+            gid: 0,
+            rdev: 0,
+            blksize: 0,
+            padding: 0,
+            flags: 0,
+        })
+    }
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                            Node                                            │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+enum Node {
+    Language,
+    Project(CString),
+}
@@ -0,0 +1,240 @@
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                                                                            │ *
+ * │ This Source Code Form is subject to the terms of the Mozilla Public                        │ *
+ * │ License, v. 2.0. If a copy of the MPL was not distributed with this                        │ *
+ * │ file, You can obtain one at http://mozilla.org/MPL/2.0/.                                   │ *
+ * │                                                                                            │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                            use                                             │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+use super::{TTL, ROOT_INO};
+use super::projects::Projects;
+use fuser::{FileAttr, FileType, ReplyAttr, ReplyData, ReplyDirectory, ReplyEntry};
+use libc::ENOENT;
+use std::collections::{BTreeMap, HashMap};
+use std::os::unix::ffi::OsStrExt;
+use std::path::PathBuf;
+use std::time::UNIX_EPOCH;
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                           const                                            │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+pub const INO: u64 = 1 << 54;
+pub const STR: &str = "links";
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                           Links                                            │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+pub struct Links {
+    ino: u64,
+    by_name: BTreeMap<String, u64>,
+    by_ino: HashMap<u64, Bundle>,
+    links: HashMap<u64, (u64, PathBuf)>,
+}
+
+impl Links {
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       new()                                        │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn new() -> Self {
+        Links {
+            ino: INO,
+            by_name: BTreeMap::new(),
+            by_ino: HashMap::new(),
+            links: HashMap::new(),
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     inherit()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn inherit(&mut self, old: &Self) {
+        self.ino = self.ino.max(old.ino);
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                    add_links()                                     │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn add_links(&mut self, project: String, links: BTreeMap<String, String>) {
+        if links.is_empty() {
+            return;
+        }
+
+        self.ino += 1;
+        let parent = self.ino;
+
+        let mut bundle = Bundle {
+            project: project.clone(),
+            by_name: BTreeMap::new(),
+        };
+
+        for (name, target) in links {
+            self.ino += 1;
+            self.links.insert(self.ino, (parent, PathBuf::from(target)));
+            bundle.by_name.insert(name, self.ino);
+        }
+
+        self.by_name.insert(project, parent);
+        self.by_ino.insert(parent, bundle);
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      index()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn index(&self, projects: &Projects) -> BTreeMap<&str, BTreeMap<&str, PathBuf>> {
+        self.by_ino
+            .values()
+            .filter_map(|bundle| {
+                let path = projects.get(&bundle.project)?;
+                let links = bundle.by_name
+                    .iter()
+                    .filter_map(|(name, ino)| {
+                        Some((name.as_str(), path.join(&self.links.get(ino)?.1)))
+                    })
+                    .collect();
+                Some((bundle.project.as_str(), links))
+            })
+            .collect()
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     getattr()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn getattr(&self, ino: u64, reply: ReplyAttr) {
+        if let Some(attr) = self.attr(ino) {
+            reply.attr(&TTL, &attr);
+        } else {
+            reply.error(ENOENT);
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      lookup()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn lookup(&self, projects: &Projects, parent: u64, name: &str, reply: ReplyEntry) {
+        let ino = if parent == ROOT_INO {
+            Some(INO)
+        } else if parent == INO {
+            projects.name(name).and_then(|name| self.by_name.get(name).copied())
+        } else {
+            self.by_ino
+                .get(&parent)
+                .and_then(|bundle| bundle.by_name.get(name).copied())
+                .filter(|ino| self.target(projects, *ino).is_some())
+        };
+
+        match ino.and_then(|ino| self.attr(ino)) {
+            Some(attr) => reply.entry(&TTL, &attr, 0),
+            None => reply.error(ENOENT),
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     readdir()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn readdir(&self, projects: &Projects, ino: u64, offset: usize, mut reply: ReplyDirectory) {
+        if ino == INO {
+            for (idx, (name, ino)) in self.by_name.iter().enumerate().skip(offset) {
+                if reply.add(*ino, (idx + 1) as i64, FileType::Directory, name) {
+                    break;
+                }
+            }
+        } else if let Some(bundle) = self.by_ino.get(&ino) {
+            let links = bundle.by_name
+                .iter()
+                .filter(|(_, ino)| self.target(projects, **ino).is_some());
+
+            for (idx, (name, ino)) in links.enumerate().skip(offset) {
+                if reply.add(*ino, (idx + 1) as i64, FileType::Symlink, name) {
+                    break;
+                }
+            }
+        } else {
+            return reply.error(ENOENT);
+        }
+
+        reply.ok();
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     readlink()                                     │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn readlink(&self, projects: &Projects, ino: u64, reply: ReplyData) {
+        match self.target(projects, ino) {
+            Some(target) => reply.data(target.as_os_str().as_bytes()),
+            None => reply.error(ENOENT),
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      target()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn target(&self, projects: &Projects, ino: u64) -> Option<PathBuf> {
+        let (parent, target) = self.links.get(&ino)?;
+        let target = projects.get(&self.by_ino.get(parent)?.project)?.join(target);
+
+        if target.exists() {
+            Some(target)
+        } else {
+            None
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       attr()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn attr(&self, ino: u64) -> Option<FileAttr> {
+        let (kind, nlink) = if ino == INO {
+            (FileType::Directory, 1 + self.by_name.len() as u32)
+        } else if let Some(bundle) = self.by_ino.get(&ino) {
+            (FileType::Directory, 1 + bundle.by_name.len() as u32)
+        } else if self.links.contains_key(&ino) {
+            (FileType::Symlink, 1)
+        } else {
+            return None;
+        };
+
+        Some(FileAttr {
+            ino,
+            size: 0,
+            blocks: 0,
+            atime: UNIX_EPOCH,
+            mtime: UNIX_EPOCH,
+            ctime: UNIX_EPOCH,
+            crtime: UNIX_EPOCH,
+            kind,
+            perm: 0o555,
+            nlink,
+            uid: 0,
+            gid: 0,
+            rdev: 0,
+            blksize: 0,
+            padding: 0,
+            flags: 0,
+        })
+    }
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                           Bundle                                           │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+struct Bundle {
+    project: String,
+    by_name: BTreeMap<String, u64>,
+}
@@ -16,31 +16,69 @@
  * │                                            mod                                             │ *
 \* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
 
+mod artifacts;
+mod at;
 mod config;
+mod control;
+mod crates;
+mod deps;
+mod frequent;
+mod git;
 mod groups;
+mod info;
+mod lang;
+mod links;
 mod mirrors;
+mod overlay;
+mod passthrough;
 mod projects;
+mod providers;
+mod recent;
+mod remotes;
+mod search;
+mod usage;
+mod watcher;
+mod worktrees;
 
 /* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
  * │                                            use                                             │ *
 \* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
 
+use self::artifacts::{INO as ARTIFACTS_INO, STR as ARTIFACTS_STR, Artifacts};
+use self::at::{INO as AT_INO, STR as AT_STR, At};
 use self::config::Config;
+use self::control::{INO as CONTROL_INO, STR as CONTROL_STR, Control, Namespace, Trigger};
+use self::crates::{INO as CRATES_INO, STR as CRATES_STR, Crates};
+use self::deps::{INO as DEPS_INO, STR as DEPS_STR, Deps};
+use self::frequent::{INO as FREQUENT_INO, STR as FREQUENT_STR, Frequent};
+use self::git::{INO as GIT_INO, STR as GIT_STR, Git};
 use self::groups::{INO as GROUPS_INO, STR as GROUPS_STR, Group, Groups};
-use self::mirrors::{INO as MIRRORS_INO, STR as MIRRORS_STR, Mirrors};
+use self::info::{INO as INFO_INO, STR as INFO_STR, Info};
+use self::lang::{INO as LANG_INO, STR as LANG_STR, Languages};
+use self::links::{INO as LINKS_INO, STR as LINKS_STR, Links};
+use self::mirrors::{
+    INO as MIRRORS_INO, STR as MIRRORS_STR, Bases, Layout, Mirrors, Renaming, Stale,
+};
+use self::overlay::{INO as OVERLAY_INO, STR as OVERLAY_STR, Overlay};
 use self::projects::{INO as PROJECTS_INO, STR as PROJECTS_STR, Projects};
+use self::providers::{INO as PROVIDERS_INO, Providers};
+use self::recent::{INO as RECENT_INO, STR as RECENT_STR, Activity, Recent};
+use self::remotes::{INO as REMOTES_INO, STR as REMOTES_STR, Remotes};
+use self::search::{INO as SEARCH_INO, STR as SEARCH_STR, Search};
+use self::usage::{INO as USAGE_INO, STR as USAGE_STR, Usage};
+use self::worktrees::{INO as WORKTREES_INO, STR as WORKTREES_STR, Worktrees};
 use fuser::{
     FileAttr, FileType,
     MountOption,
-    ReplyAttr, ReplyData, ReplyDirectory, ReplyEntry,
-    Request,
+    ReplyAttr, ReplyCreate, ReplyData, ReplyDirectory, ReplyEmpty, ReplyEntry, ReplyWrite,
+    ReplyXattr,
+    Request, TimeOrNow,
 };
-use libc::ENOENT;
-use regex::Regex;
-use std::collections::HashMap;
+use libc::{EACCES, EINVAL, EIO, ENODATA, ENOENT, EROFS};
+use std::collections::{BTreeMap, HashMap};
 use std::ffi::OsStr;
 use std::path::PathBuf;
-use std::time::{Duration, UNIX_EPOCH};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 /* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
  * │                                           const                                            │ *
@@ -49,6 +87,28 @@ use std::time::{Duration, UNIX_EPOCH};
 const TTL: Duration = Duration::from_secs(1);
 const ROOT_INO: u64 = 1;
 
+const ENTRIES: [(u64, &str); 19] = [
+    (GROUPS_INO, GROUPS_STR),
+    (PROJECTS_INO, PROJECTS_STR),
+    (MIRRORS_INO, MIRRORS_STR),
+    (OVERLAY_INO, OVERLAY_STR),
+    (RECENT_INO, RECENT_STR),
+    (FREQUENT_INO, FREQUENT_STR),
+    (GIT_INO, GIT_STR),
+    (WORKTREES_INO, WORKTREES_STR),
+    (LANG_INO, LANG_STR),
+    (LINKS_INO, LINKS_STR),
+    (REMOTES_INO, REMOTES_STR),
+    (SEARCH_INO, SEARCH_STR),
+    (INFO_INO, INFO_STR),
+    (CONTROL_INO, CONTROL_STR),
+    (AT_INO, AT_STR),
+    (CRATES_INO, CRATES_STR),
+    (ARTIFACTS_INO, ARTIFACTS_STR),
+    (DEPS_INO, DEPS_STR),
+    (USAGE_INO, USAGE_STR),
+];
+
 /* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
  * │                                           main()                                           │ *
 \* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
@@ -66,18 +126,38 @@ fn main() {
         .opt_value_from_str(["-m", "--mount"])
         .unwrap()
         .unwrap_or("/code".into());
+    let state: PathBuf = args
+        .opt_value_from_str(["-s", "--state"])
+        .unwrap()
+        .unwrap_or_else(default_state);
+    let rw = args.contains(["-w", "--rw"]);
 
-    let mut fs = FileSystem::new();
-    let config = std::fs::read(projects).unwrap();
-    toml::from_slice::<Config>(&config).unwrap().load_into(&mut fs);
+    let mut fs = FileSystem::new(state, projects, rw);
+    fs.reload().unwrap();
 
+    // Without `--rw`, writes are refused by the filesystem itself rather than the mount, which has
+    // to stay writable for the triggers under `.pr0j3c75/`.
     fuser::mount2(fs, mount, &[
-        MountOption::RO,
+        MountOption::RW,
         MountOption::AutoUnmount,
         MountOption::FSName("pr0j3c75".into()),
     ]).unwrap();
 }
 
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                      default_state()                                       │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+fn default_state() -> PathBuf {
+    if let Some(state) = std::env::var_os("XDG_STATE_HOME") {
+        PathBuf::from(state).join("pr0j3c75")
+    } else if let Some(home) = std::env::var_os("HOME") {
+        PathBuf::from(home).join(".local/state/pr0j3c75")
+    } else {
+        PathBuf::from("/var/lib/pr0j3c75")
+    }
+}
+
 /* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
  * │                                         FileSystem                                         │ *
 \* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
@@ -86,6 +166,24 @@ pub struct FileSystem {
     groups: Groups,
     projects: Projects,
     mirrors: Mirrors,
+    overlay: Overlay,
+    recent: Recent,
+    frequent: Frequent,
+    git: Git,
+    worktrees: Worktrees,
+    languages: Languages,
+    links: Links,
+    remotes: Remotes,
+    search: Search,
+    info: Info,
+    control: Control,
+    at: At,
+    crates: Crates,
+    artifacts: Artifacts,
+    deps: Deps,
+    usage: Usage,
+    providers: Providers,
+    state: PathBuf,
 }
 
 impl FileSystem {
@@ -93,14 +191,79 @@ impl FileSystem {
  *     │                                       new()                                        │     *
 \*     └────────────────────────────────────────────────────────────────────────────────────┘     */
 
-    fn new() -> Self {
+    fn new(state: PathBuf, config: PathBuf, writable: bool) -> Self {
         FileSystem {
             groups: Groups::new(),
             projects: Projects::new(),
             mirrors: Mirrors::new(),
+            overlay: Overlay::new(state.join(OVERLAY_STR)),
+            recent: Recent::new(),
+            frequent: Frequent::new(state.join("frequent.toml")),
+            git: Git::new(),
+            worktrees: Worktrees::new(),
+            languages: Languages::new(),
+            links: Links::new(),
+            remotes: Remotes::new(),
+            search: Search::new(),
+            info: Info::new(),
+            control: Control::new(config, writable),
+            at: At::new(),
+            crates: Crates::new(),
+            artifacts: Artifacts::new(),
+            deps: Deps::new(),
+            usage: Usage::new(),
+            providers: Providers::new(),
+            state,
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                    namespace()                                     │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn namespace(&self) -> Namespace<'_> {
+        Namespace {
+            projects: &self.projects,
+            groups: &self.groups,
+            mirrors: &self.mirrors,
+            links: &self.links,
+            crates: &self.crates,
+            deps: &self.deps,
+            git: &self.git,
+            languages: &self.languages,
+            recent: &self.recent,
+            frequent: &self.frequent,
         }
     }
 
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     inherit()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn inherit(&mut self, old: &mut FileSystem) {
+        // The kernel can hold on to inodes across a reload (a shell's cwd, open files), so every
+        // namespace continues from its old counter instead of handing the same inodes out again.
+        self.groups.inherit(&old.groups);
+        self.projects.inherit(&mut old.projects);
+        self.mirrors.inherit(&mut old.mirrors);
+        self.overlay.inherit(&mut old.overlay);
+        self.recent.inherit(&old.recent);
+        self.frequent.inherit(&old.frequent);
+        self.git.inherit(&mut old.git);
+        self.worktrees.inherit(&mut old.worktrees);
+        self.languages.inherit(&old.languages);
+        self.links.inherit(&old.links);
+        self.remotes.inherit(&old.remotes);
+        self.search.inherit(&old.search);
+        self.info.inherit(&old.info);
+        self.at.inherit(&old.at);
+        self.crates.inherit(&old.crates);
+        self.artifacts.inherit(&old.artifacts);
+        self.deps.inherit(&old.deps);
+        self.usage.inherit(&mut old.usage);
+        self.providers.inherit(&old.providers);
+    }
+
 /*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
  *     │                                    add_group()                                     │     *
 \*     └────────────────────────────────────────────────────────────────────────────────────┘     */
@@ -119,11 +282,13 @@ impl FileSystem {
         path: String,
         groups: impl Iterator<Item = u64>,
         aliases: &[String],
+        passthrough: bool,
     ) {
         let ino = self.projects.add_project(
             name.clone(),
             path,
             aliases.iter().cloned(),
+            passthrough,
         );
 
         for group in groups {
@@ -140,8 +305,166 @@ impl FileSystem {
  *     │                                    add_mirror()                                    │     *
 \*     └────────────────────────────────────────────────────────────────────────────────────┘     */
 
-    fn add_mirror(&mut self, name: String, path: String, renaming: Regex, aliases: &[String]) {
-        self.mirrors.add_mirror(name, path, renaming, aliases.iter().cloned());
+    fn add_mirror(
+        &mut self,
+        name: String,
+        bases: Bases,
+        renaming: Renaming,
+        aliases: &[String],
+        passthrough: bool,
+        layout: Layout,
+    ) {
+        self.mirrors.add_mirror(name, bases, renaming, aliases.iter().cloned(), passthrough, layout);
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                 set_mirror_stale()                                 │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn set_mirror_stale(&mut self, name: &str, stale: Stale) {
+        self.mirrors.set_stale(name, stale);
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                   add_provider()                                   │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn add_provider(&mut self, name: String, command: String, ttl: Duration, timeout: Duration) {
+        self.providers.add_provider(name, command, ttl, timeout);
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                    set_recent()                                    │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn set_recent(&mut self, count: usize, activity: Activity, scan: usize, interval: Duration) {
+        self.recent.configure(count, activity, scan, interval);
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                   set_frequent()                                   │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn set_frequent(&mut self, count: usize, ignore: Vec<String>) {
+        self.frequent.configure(count, ignore);
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     set_git()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn set_git(&mut self, interval: Duration) {
+        self.git.configure(interval);
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                  set_worktrees()                                   │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn set_worktrees(&mut self, location: Option<PathBuf>) {
+        self.worktrees.configure(location);
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                  set_languages()                                   │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn set_languages(&mut self, markers: HashMap<String, String>) {
+        self.languages.configure(markers);
+        self.languages.classify(&self.projects);
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                    add_links()                                     │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn add_links(&mut self, project: String, links: BTreeMap<String, String>) {
+        self.links.add_links(project, links);
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                   scan_remotes()                                   │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn scan_remotes(&mut self) {
+        self.remotes.scan(&self.projects);
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                    set_search()                                    │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn set_search(&mut self, paths: bool) {
+        self.search.configure(paths);
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                  set_artifacts()                                   │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn set_artifacts(&mut self, target: Option<PathBuf>) {
+        self.artifacts.configure(target);
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                    set_usage()                                     │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn set_usage(&mut self, budget: usize, interval: Duration, top: usize) {
+        self.usage.configure(budget, interval, top);
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                    scan_usage()                                    │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn scan_usage(&mut self) {
+        self.usage.update(&self.projects, &self.artifacts, &mut self.mirrors);
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     add_info()                                     │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn add_info(
+        &mut self,
+        name: String,
+        path: String,
+        aliases: Vec<String>,
+        groups: Vec<String>,
+        tags: Vec<String>,
+    ) {
+        self.info.add_project(name, path, aliases, groups, tags);
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      reload()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn reload(&mut self) -> Result<(), String> {
+        let config = match Config::read(self.control.path()) {
+            Ok(config) => config,
+            Err(err) => {
+                log::warn!("failed to reload config: {}", err);
+                self.control.reloaded(Err(err.clone()));
+                return Err(err);
+            },
+        };
+
+        self.frequent.save();
+
+        let config_path = self.control.path().to_path_buf();
+        let mut fs = FileSystem::new(self.state.clone(), config_path, self.control.writable());
+        std::mem::swap(&mut fs.control, &mut self.control);
+        fs.inherit(self);
+
+        let rendered = config.render();
+        config.load_into(&mut fs);
+        fs.control.reloaded(Ok(rendered));
+
+        *self = fs;
+
+        Ok(())
     }
 
 /*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
@@ -154,6 +477,10 @@ impl FileSystem {
 }
 
 impl fuser::Filesystem for FileSystem {
+    fn destroy(&mut self, _: &Request) {
+        self.frequent.save();
+    }
+
     fn getattr(&mut self, _: &Request, ino: u64, reply: ReplyAttr) {
         if ino == ROOT_INO {
             reply.attr(
@@ -183,45 +510,164 @@ impl fuser::Filesystem for FileSystem {
             self.projects.getattr(ino, reply);
         } else if ino & GROUPS_INO != 0 {
             self.groups.getattr(ino, reply);
+        } else if ino & OVERLAY_INO != 0 {
+            self.overlay.getattr(&self.projects, ino, reply);
+        } else if ino & RECENT_INO != 0 {
+            self.recent.getattr(ino, reply);
+        } else if ino & FREQUENT_INO != 0 {
+            self.frequent.getattr(ino, reply);
+        } else if ino & GIT_INO != 0 {
+            self.git.getattr(ino, reply);
+        } else if ino & WORKTREES_INO != 0 {
+            self.worktrees.getattr(ino, reply);
+        } else if ino & LANG_INO != 0 {
+            self.languages.getattr(ino, reply);
+        } else if ino & LINKS_INO != 0 {
+            self.links.getattr(ino, reply);
+        } else if ino & REMOTES_INO != 0 {
+            self.remotes.getattr(ino, reply);
+        } else if ino & SEARCH_INO != 0 {
+            self.search.getattr(ino, reply);
+        } else if ino & INFO_INO != 0 {
+            self.info.getattr(ino, reply);
+        } else if ino & CONTROL_INO != 0 {
+            self.control.getattr(self.namespace(), ino, reply);
+        } else if ino & AT_INO != 0 {
+            self.at.getattr(ino, reply);
+        } else if ino & CRATES_INO != 0 {
+            self.crates.getattr(ino, reply);
+        } else if ino & ARTIFACTS_INO != 0 {
+            self.artifacts.getattr(ino, reply);
+        } else if ino & DEPS_INO != 0 {
+            self.deps.getattr(ino, reply);
+        } else if ino & USAGE_INO != 0 {
+            self.usage.getattr(ino, reply);
+        } else if ino & PROVIDERS_INO != 0 {
+            self.providers.getattr(ino, reply);
         } else {
             reply.error(ENOENT);
         }
     }
 
-    fn lookup(&mut self, _: &Request, parent: u64, name: &OsStr, reply: ReplyEntry) {
-        let name = name.to_str().unwrap();
+    fn lookup(&mut self, req: &Request, parent: u64, raw: &OsStr, reply: ReplyEntry) {
+        // Only passthrough directories can contain names that aren't valid UTF-8.
+        let name = match raw.to_str() {
+            Some(name) => name,
+            None if parent & MIRRORS_INO != 0 => return self.mirrors.lookup(parent, raw, reply),
+            None if parent & PROJECTS_INO != 0 => return self.projects.lookup(parent, raw, reply),
+            None if parent & WORKTREES_INO != 0 => {
+                return self.worktrees.lookup(&self.projects, parent, raw, reply)
+            },
+            None => return reply.error(ENOENT),
+        };
+
+        // Passthrough projects are resolved by looking up what's inside them.
+        if let Some(project) = self.projects.project(parent) {
+            self.frequent.record(req, project);
+        }
+
         if parent == ROOT_INO {
             match name {
-                MIRRORS_STR => self.mirrors.lookup(parent, name, reply),
-                PROJECTS_STR => self.projects.lookup(parent, name, reply),
+                MIRRORS_STR => self.mirrors.lookup(parent, raw, reply),
+                PROJECTS_STR => self.projects.lookup(parent, raw, reply),
                 GROUPS_STR => if let Some(reply) = self.groups.lookup(parent, name, reply) {
-                    self.projects.lookup(parent, name, reply);
+                    self.projects.lookup(parent, raw, reply);
                 },
-                _ => reply.error(ENOENT),
+                OVERLAY_STR => self.overlay.lookup(&self.projects, parent, name, reply),
+                RECENT_STR => self.recent.lookup(&self.projects, parent, name, reply),
+                FREQUENT_STR => self.frequent.lookup(&self.projects, parent, name, reply),
+                GIT_STR => self.git.lookup(&self.projects, parent, name, reply),
+                WORKTREES_STR => self.worktrees.lookup(&self.projects, parent, raw, reply),
+                LANG_STR => self.languages.lookup(parent, name, reply),
+                LINKS_STR => self.links.lookup(&self.projects, parent, name, reply),
+                REMOTES_STR => self.remotes.lookup(parent, name, reply),
+                SEARCH_STR => self.search.lookup(&self.projects, &mut self.mirrors, parent, name, reply),
+                INFO_STR => self.info.lookup(parent, name, reply),
+                CONTROL_STR => self.control.lookup(self.namespace(), parent, name, reply),
+                AT_STR => self.at.lookup(&self.projects, parent, name, reply),
+                CRATES_STR => self.crates.lookup(&self.projects, parent, name, reply),
+                ARTIFACTS_STR => self.artifacts.lookup(&self.projects, parent, name, reply),
+                DEPS_STR => self.deps.lookup(parent, name, reply),
+                USAGE_STR => self.usage.lookup(
+                    &self.projects,
+                    &self.artifacts,
+                    &mut self.mirrors,
+                    parent,
+                    name,
+                    reply,
+                ),
+                _ => self.providers.lookup(parent, name, reply),
             }
         } else if parent & MIRRORS_INO != 0 {
-            self.mirrors.lookup(parent, name, reply);
+            self.mirrors.lookup(parent, raw, reply);
         } else if parent & PROJECTS_INO != 0 {
-            self.projects.lookup(parent, name, reply);
+            self.projects.lookup(parent, raw, reply);
         } else if parent & GROUPS_INO != 0 {
             if let Some(reply) = self.groups.lookup(parent, name, reply) {
-                self.projects.lookup(parent, name, reply);
+                self.projects.lookup(parent, raw, reply);
             }
+        } else if parent & OVERLAY_INO != 0 {
+            self.overlay.lookup(&self.projects, parent, name, reply);
+        } else if parent & RECENT_INO != 0 {
+            self.recent.lookup(&self.projects, parent, name, reply);
+        } else if parent & FREQUENT_INO != 0 {
+            self.frequent.lookup(&self.projects, parent, name, reply);
+        } else if parent & GIT_INO != 0 {
+            self.git.lookup(&self.projects, parent, name, reply);
+        } else if parent & WORKTREES_INO != 0 {
+            self.worktrees.lookup(&self.projects, parent, raw, reply);
+        } else if parent & LANG_INO != 0 {
+            self.languages.lookup(parent, name, reply);
+        } else if parent & LINKS_INO != 0 {
+            self.links.lookup(&self.projects, parent, name, reply);
+        } else if parent & REMOTES_INO != 0 {
+            self.remotes.lookup(parent, name, reply);
+        } else if parent & SEARCH_INO != 0 {
+            self.search.lookup(&self.projects, &mut self.mirrors, parent, name, reply);
+        } else if parent & INFO_INO != 0 {
+            self.info.lookup(parent, name, reply);
+        } else if parent & CONTROL_INO != 0 {
+            self.control.lookup(self.namespace(), parent, name, reply);
+        } else if parent & AT_INO != 0 {
+            self.at.lookup(&self.projects, parent, name, reply);
+        } else if parent & CRATES_INO != 0 {
+            self.crates.lookup(&self.projects, parent, name, reply);
+        } else if parent & ARTIFACTS_INO != 0 {
+            self.artifacts.lookup(&self.projects, parent, name, reply);
+        } else if parent & DEPS_INO != 0 {
+            self.deps.lookup(parent, name, reply);
+        } else if parent & USAGE_INO != 0 {
+            self.usage.lookup(
+                &self.projects,
+                &self.artifacts,
+                &mut self.mirrors,
+                parent,
+                name,
+                reply,
+            );
+        } else if parent & PROVIDERS_INO != 0 {
+            self.providers.lookup(parent, name, reply);
         } else {
             reply.error(ENOENT);
         }
     }
 
-    fn readdir(&mut self, _: &Request, ino: u64, _: u64, offset: i64, mut reply: ReplyDirectory) {
+    // Only passthrough directories hand out inodes that can be freed.
+    fn forget(&mut self, _: &Request, ino: u64, nlookup: u64) {
+        if ino & MIRRORS_INO != 0 {
+            self.mirrors.forget(ino, nlookup);
+        } else if ino & PROJECTS_INO != 0 {
+            self.projects.forget(ino, nlookup);
+        } else if ino & WORKTREES_INO != 0 {
+            self.worktrees.forget(ino, nlookup);
+        }
+    }
+
+    fn readdir(&mut self, req: &Request, ino: u64, _: u64, offset: i64, mut reply: ReplyDirectory) {
         if ino == ROOT_INO {
-            const ENTRIES: [(u64, &str); 3] = [
-                (GROUPS_INO, GROUPS_STR),
-                (PROJECTS_INO, PROJECTS_STR),
-                (MIRRORS_INO, MIRRORS_STR),
-            ];
-
-            for (idx, (ino, name)) in ENTRIES.iter().enumerate().skip(offset as usize) {
-                if reply.add(*ino, (idx + 1) as i64, FileType::Symlink, name) {
+            let entries = ENTRIES.iter().copied().chain(self.providers.iter());
+            for (idx, (ino, name)) in entries.enumerate().skip(offset as usize) {
+                if reply.add(ino, (idx + 1) as i64, FileType::Symlink, name) {
                     break;
                 }
             }
@@ -230,21 +676,367 @@ impl fuser::Filesystem for FileSystem {
         } else if ino & MIRRORS_INO != 0 {
             self.mirrors.readdir(ino, offset as usize, reply);
         } else if ino & PROJECTS_INO != 0 {
+            if ino == PROJECTS_INO {
+                self.frequent.listed(req);
+            } else if let Some(project) = self.projects.project(ino) {
+                self.frequent.record(req, project);
+            }
+
             self.projects.readdir(ino, offset as usize, reply);
         } else if ino & GROUPS_INO != 0 {
+            self.frequent.listed(req);
             self.groups.readdir(ino, offset as usize, reply);
+        } else if ino & OVERLAY_INO != 0 {
+            self.overlay.readdir(&self.projects, ino, offset as usize, reply);
+        } else if ino & RECENT_INO != 0 {
+            self.recent.readdir(&self.projects, ino, offset as usize, reply);
+        } else if ino & FREQUENT_INO != 0 {
+            self.frequent.readdir(&self.projects, ino, offset as usize, reply);
+        } else if ino & GIT_INO != 0 {
+            self.git.readdir(&self.projects, ino, offset as usize, reply);
+        } else if ino & WORKTREES_INO != 0 {
+            self.worktrees.readdir(&self.projects, ino, offset as usize, reply);
+        } else if ino & LANG_INO != 0 {
+            self.languages.readdir(ino, offset as usize, reply);
+        } else if ino & LINKS_INO != 0 {
+            self.links.readdir(&self.projects, ino, offset as usize, reply);
+        } else if ino & REMOTES_INO != 0 {
+            self.remotes.readdir(ino, offset as usize, reply);
+        } else if ino & SEARCH_INO != 0 {
+            self.search.readdir(&self.projects, &mut self.mirrors, ino, offset as usize, reply);
+        } else if ino & INFO_INO != 0 {
+            self.info.readdir(ino, offset as usize, reply);
+        } else if ino & CONTROL_INO != 0 {
+            self.control.readdir(ino, offset as usize, reply);
+        } else if ino & AT_INO != 0 {
+            self.at.readdir(&self.projects, ino, offset as usize, reply);
+        } else if ino & CRATES_INO != 0 {
+            self.crates.readdir(&self.projects, ino, offset as usize, reply);
+        } else if ino & ARTIFACTS_INO != 0 {
+            self.artifacts.readdir(&self.projects, ino, offset as usize, reply);
+        } else if ino & DEPS_INO != 0 {
+            self.deps.readdir(ino, offset as usize, reply);
+        } else if ino & USAGE_INO != 0 {
+            self.usage.readdir(
+                &self.projects,
+                &self.artifacts,
+                &mut self.mirrors,
+                ino,
+                offset as usize,
+                reply,
+            );
+        } else if ino & PROVIDERS_INO != 0 {
+            self.providers.readdir(ino, offset as usize, reply);
         } else {
             reply.error(ENOENT);
         }
     }
 
-    fn readlink(&mut self, _: &Request, ino: u64, reply: ReplyData) {
+    fn readlink(&mut self, req: &Request, ino: u64, reply: ReplyData) {
         if ino & MIRRORS_INO != 0 {
             self.mirrors.readlink(ino, reply);
         } else if ino & PROJECTS_INO != 0 {
+            if let Some(project) = self.projects.project(ino) {
+                self.frequent.record(req, project);
+            }
+
             self.projects.readlink(ino, reply);
+        } else if ino & OVERLAY_INO != 0 {
+            self.overlay.readlink(&self.projects, ino, reply);
+        } else if ino & RECENT_INO != 0 {
+            self.recent.readlink(ino, reply);
+        } else if ino & FREQUENT_INO != 0 {
+            self.frequent.readlink(&self.projects, ino, reply);
+        } else if ino & GIT_INO != 0 {
+            self.git.readlink(&self.projects, ino, reply);
+        } else if ino & WORKTREES_INO != 0 {
+            self.worktrees.readlink(ino, reply);
+        } else if ino & LANG_INO != 0 {
+            self.languages.readlink(ino, reply);
+        } else if ino & LINKS_INO != 0 {
+            self.links.readlink(&self.projects, ino, reply);
+        } else if ino & REMOTES_INO != 0 {
+            self.remotes.readlink(ino, reply);
+        } else if ino & SEARCH_INO != 0 {
+            self.search.readlink(ino, reply);
+        } else if ino & AT_INO != 0 {
+            self.at.readlink(&self.projects, ino, reply);
+        } else if ino & CRATES_INO != 0 {
+            self.crates.readlink(ino, reply);
+        } else if ino & ARTIFACTS_INO != 0 {
+            self.artifacts.readlink(ino, reply);
+        } else if ino & DEPS_INO != 0 {
+            self.deps.readlink(ino, reply);
+        } else if ino & USAGE_INO != 0 {
+            self.usage.readlink(ino, reply);
+        } else if ino & PROVIDERS_INO != 0 {
+            self.providers.readlink(ino, reply);
         } else {
             reply.error(ENOENT);
         }
     }
+
+    fn read(
+        &mut self,
+        _: &Request,
+        ino: u64,
+        _: u64,
+        offset: i64,
+        size: u32,
+        _: i32,
+        _: Option<u64>,
+        reply: ReplyData,
+    ) {
+        if ino & MIRRORS_INO != 0 {
+            self.mirrors.read(ino, offset, size, reply);
+        } else if ino & PROJECTS_INO != 0 {
+            self.projects.read(ino, offset, size, reply);
+        } else if ino & OVERLAY_INO != 0 {
+            self.overlay.read(&self.projects, ino, offset, size, reply);
+        } else if ino & WORKTREES_INO != 0 {
+            self.worktrees.read(ino, offset, size, reply);
+        } else if ino & INFO_INO != 0 {
+            self.info.read(ino, offset, size, reply);
+        } else if ino & CONTROL_INO != 0 {
+            self.control.read(self.namespace(), ino, offset, size, reply);
+        } else if ino & AT_INO != 0 {
+            self.at.read(&self.projects, ino, offset, size, reply);
+        } else if ino & USAGE_INO != 0 {
+            self.usage.read(ino, offset, size, reply);
+        } else if ino & PROVIDERS_INO != 0 {
+            self.providers.read(ino, offset, size, reply);
+        } else {
+            reply.error(ENOENT);
+        }
+    }
+
+    fn write(
+        &mut self,
+        _: &Request,
+        ino: u64,
+        _: u64,
+        offset: i64,
+        data: &[u8],
+        _: u32,
+        _: i32,
+        _: Option<u64>,
+        reply: ReplyWrite,
+    ) {
+        if ino & CONTROL_INO != 0 {
+            let result = match self.control.trigger(ino) {
+                Some(Trigger::Reload) => self.reload().map_err(|_| EINVAL),
+                Some(Trigger::RescanMirrors) => self.mirrors.rescan().map_err(|err| {
+                    log::warn!("failed to rescan mirrors: {}", err);
+                    EIO
+                }),
+                None => Err(EACCES),
+            };
+
+            match result {
+                Ok(()) => reply.written(data.len() as u32),
+                Err(err) => reply.error(err),
+            }
+        } else if !self.control.writable() {
+            reply.error(EROFS);
+        } else if ino & MIRRORS_INO != 0 {
+            self.mirrors.write(ino, offset, data, reply);
+        } else if ino & PROJECTS_INO != 0 {
+            self.projects.write(ino, offset, data, reply);
+        } else if ino & OVERLAY_INO != 0 {
+            self.overlay.write(&self.projects, ino, offset, data, reply);
+        } else if ino & WORKTREES_INO != 0 {
+            self.worktrees.write(ino, offset, data, reply);
+        } else {
+            reply.error(EACCES);
+        }
+    }
+
+    fn setattr(
+        &mut self,
+        _: &Request,
+        ino: u64,
+        mode: Option<u32>,
+        _: Option<u32>,
+        _: Option<u32>,
+        size: Option<u64>,
+        atime: Option<TimeOrNow>,
+        mtime: Option<TimeOrNow>,
+        _: Option<SystemTime>,
+        _: Option<u64>,
+        _: Option<SystemTime>,
+        _: Option<SystemTime>,
+        _: Option<SystemTime>,
+        _: Option<u32>,
+        reply: ReplyAttr,
+    ) {
+        // Truncating a trigger (`echo > reload`) goes through here before the write does.
+        if ino & CONTROL_INO != 0 && self.control.trigger(ino).is_some() {
+            self.control.getattr(self.namespace(), ino, reply);
+        } else if !self.control.writable() {
+            reply.error(EROFS);
+        } else if ino & MIRRORS_INO != 0 {
+            self.mirrors.setattr(ino, mode, size, (atime, mtime), reply);
+        } else if ino & PROJECTS_INO != 0 {
+            self.projects.setattr(ino, mode, size, (atime, mtime), reply);
+        } else if ino & OVERLAY_INO != 0 {
+            self.overlay.setattr(&self.projects, ino, mode, size, (atime, mtime), reply);
+        } else if ino & WORKTREES_INO != 0 {
+            self.worktrees.setattr(ino, mode, size, (atime, mtime), reply);
+        } else {
+            reply.error(EACCES);
+        }
+    }
+
+    fn create(
+        &mut self,
+        _: &Request,
+        parent: u64,
+        name: &OsStr,
+        mode: u32,
+        _: u32,
+        _: i32,
+        reply: ReplyCreate,
+    ) {
+        if !self.control.writable() {
+            return reply.error(EROFS);
+        }
+
+        if parent & MIRRORS_INO != 0 {
+            self.mirrors.create(parent, name, mode, reply);
+        } else if parent & PROJECTS_INO != 0 {
+            self.projects.create(parent, name, mode, reply);
+        } else if parent & OVERLAY_INO != 0 {
+            match name.to_str() {
+                Some(name) => self.overlay.create(&self.projects, parent, name, mode, reply),
+                None => reply.error(EINVAL),
+            }
+        } else if parent & WORKTREES_INO != 0 {
+            self.worktrees.create(parent, name, mode, reply);
+        } else {
+            reply.error(EACCES);
+        }
+    }
+
+    fn mkdir(
+        &mut self,
+        _: &Request,
+        parent: u64,
+        name: &OsStr,
+        mode: u32,
+        _: u32,
+        reply: ReplyEntry,
+    ) {
+        if !self.control.writable() {
+            return reply.error(EROFS);
+        }
+
+        if parent & MIRRORS_INO != 0 {
+            self.mirrors.mkdir(parent, name, mode, reply);
+        } else if parent & PROJECTS_INO != 0 {
+            self.projects.mkdir(parent, name, mode, reply);
+        } else if parent & OVERLAY_INO != 0 {
+            match name.to_str() {
+                Some(name) => self.overlay.mkdir(&self.projects, parent, name, mode, reply),
+                None => reply.error(EINVAL),
+            }
+        } else if parent & WORKTREES_INO != 0 {
+            self.worktrees.mkdir(&self.projects, parent, name, mode, reply);
+        } else {
+            reply.error(EACCES);
+        }
+    }
+
+    fn unlink(&mut self, _: &Request, parent: u64, name: &OsStr, reply: ReplyEmpty) {
+        if !self.control.writable() {
+            return reply.error(EROFS);
+        }
+
+        if parent & MIRRORS_INO != 0 {
+            self.mirrors.unlink(parent, name, reply);
+        } else if parent & PROJECTS_INO != 0 {
+            self.projects.unlink(parent, name, reply);
+        } else if parent & OVERLAY_INO != 0 {
+            match name.to_str() {
+                Some(name) => self.overlay.unlink(&self.projects, parent, name, reply),
+                None => reply.error(EINVAL),
+            }
+        } else if parent & WORKTREES_INO != 0 {
+            self.worktrees.unlink(parent, name, reply);
+        } else {
+            reply.error(EACCES);
+        }
+    }
+
+    fn rmdir(&mut self, _: &Request, parent: u64, name: &OsStr, reply: ReplyEmpty) {
+        if !self.control.writable() {
+            return reply.error(EROFS);
+        }
+
+        if parent & MIRRORS_INO != 0 {
+            self.mirrors.rmdir(parent, name, reply);
+        } else if parent & PROJECTS_INO != 0 {
+            self.projects.rmdir(parent, name, reply);
+        } else if parent & OVERLAY_INO != 0 {
+            match name.to_str() {
+                Some(name) => self.overlay.rmdir(&self.projects, parent, name, reply),
+                None => reply.error(EINVAL),
+            }
+        } else if parent & WORKTREES_INO != 0 {
+            self.worktrees.rmdir(parent, name, reply);
+        } else {
+            reply.error(EACCES);
+        }
+    }
+
+    fn rename(
+        &mut self,
+        _: &Request,
+        parent: u64,
+        name: &OsStr,
+        new_parent: u64,
+        new_name: &OsStr,
+        _: u32,
+        reply: ReplyEmpty,
+    ) {
+        if !self.control.writable() {
+            return reply.error(EROFS);
+        }
+
+        if parent & MIRRORS_INO != 0 && new_parent & MIRRORS_INO != 0 {
+            self.mirrors.rename(parent, name, new_parent, new_name, reply);
+        } else if parent & PROJECTS_INO != 0 && new_parent & PROJECTS_INO != 0 {
+            self.projects.rename(parent, name, new_parent, new_name, reply);
+        } else if parent & OVERLAY_INO != 0 && new_parent & OVERLAY_INO != 0 {
+            match (name.to_str(), new_name.to_str()) {
+                (Some(name), Some(new_name)) => {
+                    self.overlay.rename(&self.projects, parent, name, new_parent, new_name, reply)
+                },
+                _ => reply.error(EINVAL),
+            }
+        } else if parent & WORKTREES_INO != 0 && new_parent & WORKTREES_INO != 0 {
+            self.worktrees.rename(parent, name, new_parent, new_name, reply);
+        } else {
+            reply.error(EACCES);
+        }
+    }
+
+    // Only `usage/` has extended attributes, but the kernel stops asking for them altogether once
+    // these reply `ENOSYS`.
+    fn getxattr(&mut self, _: &Request, ino: u64, name: &OsStr, size: u32, reply: ReplyXattr) {
+        if ino & USAGE_INO != 0 {
+            self.usage.getxattr(ino, &name.to_string_lossy(), size, reply);
+        } else {
+            reply.error(ENODATA);
+        }
+    }
+
+    fn listxattr(&mut self, _: &Request, ino: u64, size: u32, reply: ReplyXattr) {
+        if ino & USAGE_INO != 0 {
+            self.usage.listxattr(ino, size, reply);
+        } else if size == 0 {
+            reply.size(0);
+        } else {
+            reply.data(&[]);
+        }
+    }
 }
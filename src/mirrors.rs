@@ -11,14 +11,24 @@
 \* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
 
 use super::{TTL, ROOT_INO};
-use fuser::{FileAttr, FileType, ReplyAttr, ReplyData, ReplyDirectory, ReplyEntry};
-use libc::ENOENT;
+use super::passthrough::{self, Passthrough};
+use super::watcher::Watcher;
+use fuser::{
+    FileAttr, FileType,
+    ReplyAttr, ReplyCreate, ReplyData, ReplyDirectory, ReplyEmpty, ReplyEntry, ReplyWrite,
+    TimeOrNow,
+};
+use libc::{EACCES, ENOENT};
 use regex::Regex;
-use std::collections::HashMap;
-use std::ffi::{CString, OsString};
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::collections::hash_map::Entry;
+use std::ffi::{CString, OsStr};
 use std::fs;
-use std::path::PathBuf;
-use std::time::{UNIX_EPOCH, SystemTime};
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, UNIX_EPOCH, SystemTime};
 
 /* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
  * │                                           const                                            │ *
@@ -27,6 +37,516 @@ use std::time::{UNIX_EPOCH, SystemTime};
 pub const INO: u64 = 1 << 63;
 pub const STR: &str = "mirrors";
 
+const LATEST: &str = "latest";
+const STALE: &str = ".stale";
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                         Collision                                          │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+#[derive(Clone, Copy, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Collision {
+    Newest,
+    Oldest,
+    Error,
+    Suffix,
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                         Transform                                          │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+#[derive(Clone, Copy, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Transform {
+    Lowercase,
+    Uppercase,
+    Trim,
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                         EntryType                                          │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+#[derive(Clone, Copy, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum EntryType {
+    Dir,
+    File,
+    Symlink,
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                          Filters                                           │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+#[derive(Clone, Default, Deserialize, Serialize)]
+pub struct Filters {
+    #[serde(default)]
+    types: Vec<EntryType>,
+    #[serde(default)]
+    include: Vec<String>,
+    #[serde(default)]
+    exclude: Vec<String>,
+    max_age: Option<u64>,
+    newest: Option<usize>,
+    min_size: Option<u64>,
+    max_size: Option<u64>,
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                           Filter                                           │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+struct Filter {
+    types: Vec<EntryType>,
+    include: Vec<Pattern>,
+    exclude: Vec<Pattern>,
+    max_age: Option<Duration>,
+    newest: Option<usize>,
+    min_size: Option<u64>,
+    max_size: Option<u64>,
+}
+
+impl Filter {
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       new()                                        │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn new(filters: &Filters) -> Result<Self, String> {
+        let patterns = |patterns: &[String]| {
+            patterns.iter().map(|pattern| Pattern::new(pattern)).collect::<Result<_, _>>()
+        };
+
+        let types = if filters.types.is_empty() {
+            vec![EntryType::Dir]
+        } else {
+            filters.types.clone()
+        };
+
+        Ok(Filter {
+            types,
+            include: patterns(&filters.include)?,
+            exclude: patterns(&filters.exclude)?,
+            max_age: filters.max_age.map(|days| Duration::from_secs(days * 24 * 60 * 60)),
+            newest: filters.newest,
+            min_size: filters.min_size,
+            max_size: filters.max_size,
+        })
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     excludes()                                     │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn excludes(&self, name: &str) -> bool {
+        self.exclude.iter().any(|pattern| pattern.matches(name))
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     matches()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    // Sizes are only checked for regular files, the size of a directory doesn't mean much.
+    fn matches(&self, name: &str, metadata: &fs::Metadata) -> bool {
+        let kind = metadata.file_type();
+        let kind = if kind.is_dir() {
+            EntryType::Dir
+        } else if kind.is_symlink() {
+            EntryType::Symlink
+        } else {
+            EntryType::File
+        };
+
+        let age = metadata.modified().ok().and_then(|mtime| mtime.elapsed().ok());
+        let size = Some(metadata.len()).filter(|_| metadata.is_file());
+
+        self.types.contains(&kind)
+            && (self.include.is_empty() || self.include.iter().any(|p| p.matches(name)))
+            && !matches!((self.max_age, age), (Some(max), Some(age)) if age > max)
+            && !matches!((self.min_size, size), (Some(min), Some(size)) if size < min)
+            && !matches!((self.max_size, size), (Some(max), Some(size)) if size > max)
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      newest()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn newest(&self, renamed: &mut BTreeMap<String, Vec<Candidate>>) {
+        let count = match self.newest {
+            Some(count) => count,
+            None => return,
+        };
+
+        let mut mtimes = renamed
+            .values()
+            .flatten()
+            .map(|candidate| candidate.modified)
+            .collect::<Vec<_>>();
+        if mtimes.len() <= count {
+            return;
+        }
+
+        mtimes.sort_unstable_by(|a, b| b.cmp(a));
+
+        // Entries sharing the cutoff time are all kept, rather than picking some arbitrarily.
+        let cutoff = match mtimes.get(count.saturating_sub(1)) {
+            Some(cutoff) if count > 0 => *cutoff,
+            _ => return renamed.clear(),
+        };
+
+        for candidates in renamed.values_mut() {
+            candidates.retain(|candidate| candidate.modified >= cutoff);
+        }
+
+        renamed.retain(|_, candidates| !candidates.is_empty());
+    }
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                          Pattern                                           │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+enum Pattern {
+    Glob(glob::Pattern),
+    Regex(Regex),
+}
+
+impl Pattern {
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       new()                                        │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn new(pattern: &str) -> Result<Self, String> {
+        match pattern.strip_prefix("re:") {
+            Some(regex) => Regex::new(regex).map(Pattern::Regex).map_err(|err| err.to_string()),
+            None => glob::Pattern::new(pattern).map(Pattern::Glob).map_err(|err| err.to_string()),
+        }
+        .map_err(|err| format!("{}: {}", pattern, err))
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     matches()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn matches(&self, name: &str) -> bool {
+        match self {
+            Pattern::Glob(glob) => glob.matches(name),
+            Pattern::Regex(regex) => regex.is_match(name),
+        }
+    }
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                           Bases                                            │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+pub struct Bases {
+    patterns: Vec<String>,
+    depth: usize,
+    filter: Filter,
+}
+
+impl Bases {
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       new()                                        │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn new(patterns: Vec<String>, depth: usize, filters: &Filters) -> Result<Self, String> {
+        if depth == 0 {
+            return Err("`depth` must be at least 1".into());
+        }
+
+        for pattern in &patterns {
+            let expanded = expand_home(pattern);
+            if !is_glob(&expanded) {
+                fs::metadata(&expanded).map_err(|err| format!("{}: {}", pattern, err))?;
+            } else if expanded.contains("**") {
+                return Err(format!("{}: `**` isn't supported, use `depth` instead", pattern));
+            } else {
+                glob::Pattern::new(&expanded).map_err(|err| format!("{}: {}", pattern, err))?;
+            }
+        }
+
+        Ok(Bases { patterns, depth, filter: Filter::new(filters)? })
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      expand()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    // Every wildcard segment of a pattern becomes a directory level, named after what it matched.
+    fn expand(&self) -> Vec<(Vec<String>, PathBuf)> {
+        let mut bases = Vec::new();
+
+        for pattern in &self.patterns {
+            let pattern = expand_home(pattern);
+            if !is_glob(&pattern) {
+                bases.push((Vec::new(), PathBuf::from(pattern)));
+                continue;
+            }
+
+            let wildcards = Path::new(&pattern)
+                .components()
+                .enumerate()
+                .filter(|(_, component)| is_glob(&component.as_os_str().to_string_lossy()))
+                .map(|(idx, _)| idx)
+                .collect::<Vec<_>>();
+
+            let matches = glob::glob(&pattern).into_iter().flatten().filter_map(Result::ok);
+            for base in matches.filter(|base| base.is_dir()) {
+                let components = base.components().collect::<Vec<_>>();
+                let levels = wildcards
+                    .iter()
+                    .filter_map(|idx| components.get(*idx)?.as_os_str().to_str())
+                    .map(str::to_string)
+                    .collect();
+
+                bases.push((levels, base));
+            }
+        }
+
+        bases.sort_unstable();
+        bases
+    }
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                          Renaming                                          │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+pub struct Renaming {
+    regex: Regex,
+    template: Option<String>,
+    transforms: Vec<Transform>,
+}
+
+impl Renaming {
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       new()                                        │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn new(
+        regex: &str,
+        template: Option<String>,
+        transforms: Vec<Transform>,
+    ) -> Result<Self, String> {
+        let regex = Regex::new(regex).map_err(|err| err.to_string())?;
+
+        if let Some(template) = &template {
+            let mut chars = template.char_indices().peekable();
+            while let Some((_, chr)) = chars.next() {
+                if chr != '$' {
+                    continue;
+                }
+
+                // Mirrors `Captures::expand`: `$$` is a literal dollar, `${name}` is braced and
+                // `$name` is the longest run of `[_0-9a-zA-Z]`.
+                let name = match chars.peek() {
+                    Some((_, '$')) => {
+                        chars.next();
+                        continue;
+                    },
+                    Some((_, '{')) => {
+                        chars.next();
+                        chars.by_ref().map(|(_, chr)| chr).take_while(|chr| *chr != '}').collect()
+                    },
+                    _ => {
+                        let mut name = String::new();
+                        let is_name = |(_, chr): &(usize, char)| {
+                            *chr == '_' || chr.is_ascii_alphanumeric()
+                        };
+                        while let Some((_, chr)) = chars.next_if(is_name) {
+                            name.push(chr);
+                        }
+
+                        name
+                    },
+                };
+
+                if name.is_empty() {
+                    continue;
+                }
+
+                if !has_capture(&regex, &name) {
+                    return Err(format!("unknown capture group `{}` in `{}`", name, template));
+                }
+            }
+        }
+
+        Ok(Renaming { regex, template, transforms })
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      rename()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn rename(&self, name: &str) -> Option<String> {
+        let captures = self.regex.captures(name)?;
+
+        let mut renamed = match &self.template {
+            Some(template) => {
+                let mut renamed = String::new();
+                captures.expand(template, &mut renamed);
+                renamed
+            },
+            None => captures.get(captures.len() - 1).unwrap().as_str().to_string(),
+        };
+
+        for transform in &self.transforms {
+            renamed = match transform {
+                Transform::Lowercase => renamed.to_lowercase(),
+                Transform::Uppercase => renamed.to_uppercase(),
+                Transform::Trim => {
+                    let trim = |chr: char| chr.is_whitespace() || "-_.".contains(chr);
+                    renamed.trim_matches(trim).into()
+                },
+            };
+        }
+
+        if renamed.is_empty() || renamed.contains('/') {
+            None
+        } else {
+            Some(renamed)
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     capture()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn capture(&self, name: &str, group: &str) -> Option<String> {
+        let captures = self.regex.captures(name)?;
+        let capture = match group.parse::<usize>() {
+            Ok(idx) => captures.get(idx),
+            Err(_) => captures.name(group),
+        };
+
+        capture.map(|capture| capture.as_str().to_string())
+    }
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                           Layout                                           │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+pub enum Layout {
+    Collapsed(Collision),
+    Versioned(Versions),
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                          Versions                                          │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+#[derive(Clone, Copy, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum VersionName {
+    Original,
+    Mtime,
+    Capture,
+}
+
+#[derive(Clone, Copy, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Latest {
+    Mtime,
+    Semver,
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct Versions {
+    name: Option<VersionName>,
+    capture: Option<String>,
+    latest: Option<Latest>,
+}
+
+impl Versions {
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      check()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn check(&self, renaming: &Renaming) -> Result<(), String> {
+        let needs_capture =
+            self.name == Some(VersionName::Capture) || self.latest == Some(Latest::Semver);
+
+        match &self.capture {
+            Some(group) if !has_capture(&renaming.regex, group) => {
+                Err(format!("unknown capture group `{}`", group))
+            },
+            None if needs_capture => Err("`capture` is required by `name` or `latest`".into()),
+            _ => Ok(()),
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       name()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn name(&self, candidate: &Candidate) -> String {
+        match self.name.unwrap_or(VersionName::Original) {
+            VersionName::Original => candidate.original.clone(),
+            VersionName::Mtime => {
+                let secs = candidate.modified.duration_since(UNIX_EPOCH).unwrap_or_default();
+                secs.as_secs().to_string()
+            },
+            VersionName::Capture => {
+                candidate.capture.clone().unwrap_or_else(|| candidate.original.clone())
+            },
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      latest()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    // Entries whose capture isn't a valid version sort before those whose is, ties are broken by
+    // modification time.
+    fn latest<'a>(&self, candidates: &'a [Candidate]) -> Option<&'a Candidate> {
+        match self.latest.unwrap_or(Latest::Mtime) {
+            Latest::Mtime => candidates.iter().max_by_key(|candidate| candidate.modified),
+            Latest::Semver => candidates.iter().max_by_key(|candidate| {
+                let version = candidate.capture.as_deref().and_then(|v| Version::parse(v).ok());
+                (version, candidate.modified)
+            }),
+        }
+    }
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                           Stale                                            │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct Stale {
+    max_age: Option<u64>,
+    superseded: Option<bool>,
+}
+
+impl Stale {
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      is_old()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn is_old(&self, candidate: &Candidate) -> bool {
+        let max_age = self.max_age.map(|days| Duration::from_secs(days * 24 * 60 * 60));
+        matches!(
+            (max_age, candidate.modified.elapsed()),
+            (Some(max), Ok(age)) if age > max
+        )
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                  is_superseded()                                   │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    // An entry is superseded when another one renamed to the same name was modified after it.
+    fn is_superseded(&self, candidate: &Candidate, newest: SystemTime) -> bool {
+        self.superseded.unwrap_or(false) && candidate.modified < newest
+    }
+}
+
 /* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
  * │                                          Mirrors                                           │ *
 \* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
@@ -36,6 +556,9 @@ pub struct Mirrors {
     by_name: HashMap<String, u64>,
     by_ino: HashMap<u64, Mirror>,
     rec_by_ino: HashMap<u64, u64>,
+    previous: HashMap<String, u64>,
+    passthrough: Passthrough,
+    watcher: Option<Watcher>,
 }
 
 impl Mirrors {
@@ -49,122 +572,528 @@ impl Mirrors {
             by_name: HashMap::new(),
             by_ino: HashMap::new(),
             rec_by_ino: HashMap::new(),
+            previous: HashMap::new(),
+            passthrough: Passthrough::new(),
+            watcher: match Watcher::new() {
+                Ok(watcher) => Some(watcher),
+                Err(err) => {
+                    log::warn!("failed to initialize inotify, mirrors refresh on lookups: {}", err);
+                    None
+                },
+            },
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     inherit()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn inherit(&mut self, old: &mut Self) {
+        // Mirror roots keep their inode across a reload, the entries inside them are rebuilt.
+        self.ino = self.ino.max(old.ino);
+        self.previous = old
+            .by_ino
+            .iter()
+            .map(|(&ino, mirror)| (mirror.name.clone(), ino))
+            .collect();
+        std::mem::swap(&mut self.passthrough, &mut old.passthrough);
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                    add_mirror()                                    │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn add_mirror(
+        &mut self,
+        name: String,
+        bases: Bases,
+        renaming: Renaming,
+        aliases: impl Iterator<Item = String>,
+        passthrough: bool,
+        layout: Layout,
+    ) -> u64 {
+        let ino = match self.previous.remove(&name) {
+            Some(ino) => ino,
+            None => {
+                self.ino += 1;
+                self.ino
+            },
+        };
+
+        self.by_name.insert(name.clone(), ino).expect_none("mirror already exists");
+
+        self.by_ino.insert(ino, Mirror {
+            ino,
+            name,
+            bases,
+            renaming,
+            passthrough,
+            layout,
+            collided: Vec::new(),
+            by_name: HashMap::new(),
+            by_ino: HashMap::new(),
+            dirs: HashMap::new(),
+            latest: HashSet::new(),
+            files: HashSet::new(),
+            stale: None,
+            stale_dir: None,
+            pruneable: HashSet::new(),
+            inos: HashMap::new(),
+            matched: Vec::new(),
+            watched: Vec::new(),
+            checked: UNIX_EPOCH,
+        });
+
+        for alias in aliases {
+            self.add_alias(alias, ino);
+        }
+
+        ino
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                    set_stale()                                     │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn set_stale(&mut self, name: &str, stale: Stale) {
+        let by_ino = &mut self.by_ino;
+        if let Some(mirror) = self.by_name.get(name).and_then(|ino| by_ino.get_mut(ino)) {
+            mirror.stale = Some(stale);
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                    add_alias()                                     │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn add_alias(&mut self, name: String, ino: u64) {
+        self.by_name.insert(name, ino).expect_none("mirror already exists");
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      update()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn update(&mut self) {
+        for mino in self.by_ino.keys().copied().collect::<Vec<_>>() {
+            self.refresh(mino);
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     entries()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn entries(&mut self) -> impl Iterator<Item = (&str, &str, &Path)> {
+        self.update();
+
+        self.by_ino.values().flat_map(|mirror| {
+            mirror.entries().into_iter().map(move |(_, name, path)| {
+                (mirror.name.as_str(), name, Path::new(OsStr::from_bytes(path.as_bytes())))
+            })
+        })
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      rescan()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn rescan(&mut self) -> Result<(), String> {
+        let mut errors = Vec::new();
+        for mino in self.by_ino.keys().copied().collect::<Vec<_>>() {
+            self.by_ino.get_mut(&mino).unwrap().watched.clear();
+            self.refresh(mino);
+
+            // A broken mirror shouldn't keep the ones after it from being refreshed.
+            let mirror = &self.by_ino[&mino];
+            let bases = mirror.bases.patterns.join(", ");
+            if mirror.matched.is_empty() {
+                errors.push(format!("{}: no matching directories", bases));
+            } else if !mirror.collided.is_empty() {
+                errors.push(format!("{}: colliding names: {}", bases, mirror.collided.join(", ")));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.join("; "))
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      index()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn index(&self) -> BTreeMap<&str, BTreeMap<String, &Path>> {
+        self.by_name
+            .iter()
+            .filter_map(|(name, ino)| {
+                let mirror = self.by_ino.get(ino)?;
+                let entries = mirror
+                    .entries()
+                    .into_iter()
+                    .map(|(mut levels, name, path)| {
+                        levels.push(name);
+                        (levels.join("/"), Path::new(OsStr::from_bytes(path.as_bytes())))
+                    })
+                    .collect();
+
+                Some((name.as_str(), entries))
+            })
+            .collect()
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     getattr()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn getattr(&self, ino: u64, reply: ReplyAttr) {
+        if let Some(path) = self.passthrough.path(ino) {
+            passthrough::getattr(ino, path, reply);
+        } else if let Some(attr) = self.attr(ino) {
+            reply.attr(&TTL, &attr);
+        } else {
+            reply.error(ENOENT);
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       lookup                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn lookup(&mut self, parent: u64, name: &OsStr, reply: ReplyEntry) {
+        self.poll();
+
+        if let Some(path) = self.path(parent) {
+            return self.passthrough.lookup(&mut self.ino, &path, name, reply);
+        }
+
+        let name = match name.to_str() {
+            Some(name) => name,
+            None => return reply.error(ENOENT),
+        };
+
+        if parent == ROOT_INO {
+            return reply.entry(&TTL, &self.attr(INO).unwrap(), 0);
+        } else if parent == INO {
+            if let Some(ino) = self.by_name.get(name).copied() {
+                return reply.entry(&TTL, &self.attr(ino).unwrap(), 0);
+            }
+        } else if let Some(attr) = self.child(parent, name) {
+            return reply.entry(&TTL, &attr, 0);
+        } else {
+            // Without inotify (or before the first scan), unknown names are the only hint that
+            // something might have appeared since. Shells probe plenty of names that never will,
+            // hence rescanning at most once per `TTL`.
+            let mino = if self.by_ino.contains_key(&parent) {
+                Some(parent)
+            } else {
+                self.rec_by_ino.get(&parent).copied()
+            };
+
+            if let Some(mirror) = mino.and_then(|mino| self.by_ino.get(&mino)) {
+                let unwatched = self.watcher.is_none() || mirror.watched.is_empty();
+                if unwatched && mirror.checked.elapsed().unwrap_or_default() > TTL {
+                    self.refresh(mirror.ino);
+                }
+            }
+
+            if let Some(attr) = self.child(parent, name) {
+                return reply.entry(&TTL, &attr, 0);
+            }
+        }
+
+        reply.error(ENOENT);
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      forget()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn forget(&mut self, ino: u64, nlookup: u64) {
+        self.passthrough.forget(ino, nlookup);
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     readdir()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn readdir(&mut self, ino: u64, offset: usize, mut reply: ReplyDirectory) {
+        self.poll();
+
+        if let Some(path) = self.path(ino) {
+            return self.passthrough.readdir(&path, offset, reply);
+        } else if ino == INO {
+            for (idx, (name, ino)) in self.by_name.iter().enumerate().skip(offset) {
+                if reply.add(*ino, (idx + 1) as i64, FileType::Directory, name) {
+                    break;
+                }
+            }
+        } else if let Some(mirror) = self.by_ino.get(&ino) {
+            if offset == 0 && mirror.checked.elapsed().unwrap_or_default() > TTL {
+                self.refresh(ino);
+            }
+
+            let mirror = &self.by_ino[&ino];
+            for (idx, (name, ino)) in mirror.by_name.iter().enumerate().skip(offset) {
+                if reply.add(*ino, (idx + 1) as i64, mirror.kind(*ino), name) {
+                    break;
+                }
+            }
+        } else if let Some(mirror) = self.owner(ino) {
+            let entries = match mirror.dirs.get(&ino) {
+                Some(entries) => entries,
+                None => return reply.error(ENOENT),
+            };
+
+            for (idx, (name, ino)) in entries.iter().enumerate().skip(offset) {
+                if reply.add(*ino, (idx + 1) as i64, mirror.kind(*ino), name) {
+                    break;
+                }
+            }
+        } else {
+            return reply.error(ENOENT);
+        }
+
+        reply.ok();
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     readlink()                                     │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn readlink(&self, ino: u64, reply: ReplyData) {
+        if let Some(path) = self.passthrough.path(ino) {
+            return passthrough::readlink(path, reply);
+        } else if let Some(mino) = self.rec_by_ino.get(&ino) {
+            if let Some(mirror) = self.by_ino.get(mino) {
+                if mirror.kind(ino) == FileType::Symlink {
+                    if let Some(path) = mirror.by_ino.get(&ino) {
+                        return reply.data(path.as_bytes());
+                    }
+                }
+            }
+        }
+
+        reply.error(ENOENT);
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       read()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn read(&self, ino: u64, offset: i64, size: u32, reply: ReplyData) {
+        match self.passthrough.path(ino) {
+            Some(path) => passthrough::read(path, offset, size, reply),
+            None => reply.error(ENOENT),
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      write()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn write(&self, ino: u64, offset: i64, data: &[u8], reply: ReplyWrite) {
+        match self.passthrough.path(ino) {
+            Some(path) => passthrough::write(path, offset, data, reply),
+            None => reply.error(EACCES),
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     setattr()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn setattr(
+        &self,
+        ino: u64,
+        mode: Option<u32>,
+        size: Option<u64>,
+        times: (Option<TimeOrNow>, Option<TimeOrNow>),
+        reply: ReplyAttr,
+    ) {
+        match self.path(ino) {
+            Some(path) => passthrough::setattr(ino, &path, mode, size, times, reply),
+            None => reply.error(EACCES),
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      create()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn create(&mut self, parent: u64, name: &OsStr, mode: u32, reply: ReplyCreate) {
+        match self.path(parent) {
+            Some(path) => self.passthrough.create(&mut self.ino, &path, name, mode, reply),
+            None => reply.error(EACCES),
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      mkdir()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn mkdir(&mut self, parent: u64, name: &OsStr, mode: u32, reply: ReplyEntry) {
+        match self.path(parent) {
+            Some(path) => self.passthrough.mkdir(&mut self.ino, &path, name, mode, reply),
+            None => reply.error(EACCES),
         }
     }
 
 /*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
- *     │                                    add_mirror()                                    │     *
+ *     │                                      unlink()                                      │     *
 \*     └────────────────────────────────────────────────────────────────────────────────────┘     */
 
-    pub fn add_mirror(
-        &mut self,
-        name: String,
-        base: String,
-        renaming: Regex,
-        aliases: impl Iterator<Item = String>,
-    ) -> u64 {
-        self.ino += 1;
+    pub fn unlink(&mut self, parent: u64, name: &OsStr, reply: ReplyEmpty) {
+        if let Some(path) = self.path(parent) {
+            return self.passthrough.unlink(&path, name, reply);
+        }
 
-        self.by_name.insert(name.clone(), self.ino).expect_none("mirror already exists");
+        let name = match name.to_str() {
+            Some(name) => name,
+            None => return reply.error(ENOENT),
+        };
 
-        self.by_ino.insert(self.ino, Mirror {
-            ino: self.ino,
-            base: PathBuf::from(base),
-            renaming,
-            by_name: HashMap::new(),
-            by_ino: HashMap::new(),
-            modified: UNIX_EPOCH,
-        });
+        // Unlinking from `.stale/` deletes what the entry points to, which is what `rm -r` does
+        // with the symlinks listed there.
+        let (mino, path) = match self.owner(parent) {
+            Some(mirror) if mirror.stale_dir == Some(parent) => {
+                let entry = mirror.dirs.get(&parent).and_then(|entries| entries.get(name));
+                match entry.and_then(|entry| mirror.by_ino.get(entry)) {
+                    Some(path) => (mirror.ino, PathBuf::from(OsStr::from_bytes(path.as_bytes()))),
+                    None => return reply.error(ENOENT),
+                }
+            },
+            _ => return reply.error(EACCES),
+        };
 
-        for alias in aliases {
-            self.add_alias(alias, self.ino);
-        }
+        let removed = match fs::symlink_metadata(&path) {
+            Ok(metadata) if metadata.is_dir() => fs::remove_dir_all(&path),
+            Ok(_) => fs::remove_file(&path),
+            Err(err) => Err(err),
+        };
 
-        self.ino
+        match removed {
+            Ok(()) => {
+                log::info!("pruned {}", path.display());
+                self.by_ino.get_mut(&mino).unwrap().watched.clear();
+                self.refresh(mino);
+                reply.ok();
+            },
+            Err(err) => {
+                log::warn!("failed to prune {}: {}", path.display(), err);
+                reply.error(passthrough::errno(err));
+            },
+        }
     }
 
 /*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
- *     │                                    add_alias()                                     │     *
+ *     │                                      rmdir()                                       │     *
 \*     └────────────────────────────────────────────────────────────────────────────────────┘     */
 
-    fn add_alias(&mut self, name: String, ino: u64) {
-        self.by_name.insert(name, ino).expect_none("mirror already exists");
+    pub fn rmdir(&mut self, parent: u64, name: &OsStr, reply: ReplyEmpty) {
+        match self.path(parent) {
+            Some(path) => self.passthrough.rmdir(&path, name, reply),
+            None => reply.error(EACCES),
+        }
     }
 
 /*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
- *     │                                     getattr()                                      │     *
+ *     │                                      rename()                                      │     *
 \*     └────────────────────────────────────────────────────────────────────────────────────┘     */
 
-    pub fn getattr(&self, ino: u64, reply: ReplyAttr) {
-        if let Some(attr) = self.attr(ino) {
-            reply.attr(&TTL, &attr);
-        } else {
-            reply.error(ENOENT);
+    pub fn rename(
+        &mut self,
+        parent: u64,
+        name: &OsStr,
+        new_parent: u64,
+        new_name: &OsStr,
+        reply: ReplyEmpty,
+    ) {
+        match (self.path(parent), self.path(new_parent)) {
+            (Some(path), Some(new_path)) => {
+                self.passthrough.rename(&path, name, &new_path, new_name, reply)
+            },
+            _ => reply.error(EACCES),
         }
     }
 
 /*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
- *     │                                       lookup                                       │     *
+ *     │                                       poll()                                       │     *
 \*     └────────────────────────────────────────────────────────────────────────────────────┘     */
 
-    pub fn lookup(&self, parent: u64, name: &str, reply: ReplyEntry) {
-        if parent == ROOT_INO {
-            return reply.entry(&TTL, &self.attr(INO).unwrap(), 0);
-        } else if parent == INO {
-            if let Some(ino) = self.by_name.get(name).copied() {
-                return reply.entry(&TTL, &self.attr(ino).unwrap(), 0);
-            }
-        } else if let Some(mirror) = self.by_ino.get(&parent) {
-            if let Some(attr) = mirror.attr(name) {
-                return reply.entry(&TTL, &attr, 0);
+    // Events are only drained when the mirrors get looked up or listed: `fuser` 0.6 has no way to
+    // invalidate the kernel's cache, so refreshing in the background wouldn't make changes show
+    // up any sooner than the entries' `TTL` does. Watching only spares rescanning on every
+    // unknown name, it doesn't push anything to the kernel.
+    fn poll(&mut self) {
+        let changed = match &mut self.watcher {
+            Some(watcher) => watcher.poll(),
+            None => return,
+        };
+
+        for mino in changed {
+            if let Some(mirror) = self.by_ino.get_mut(&mino) {
+                mirror.watched.clear();
+                self.refresh(mino);
             }
         }
-
-        reply.error(ENOENT);
     }
 
 /*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
- *     │                                     readdir()                                      │     *
+ *     │                                     refresh()                                      │     *
 \*     └────────────────────────────────────────────────────────────────────────────────────┘     */
 
-    pub fn readdir(&mut self, ino: u64, offset: usize, mut reply: ReplyDirectory) {
-        if ino == INO {
-            for (idx, (name, ino)) in self.by_name.iter().enumerate().skip(offset) {
-                if reply.add(*ino, (idx + 1) as i64, FileType::Directory, name) {
-                    break;
-                }
-            }
-        } else if let Some(mirror) = self.by_ino.get_mut(&ino) {
-            if offset == 0 && mirror.modified.elapsed().unwrap() > TTL {
-                mirror.update(&mut self.ino, &mut self.rec_by_ino);
-            }
+    fn refresh(&mut self, mino: u64) {
+        let mirror = match self.by_ino.get_mut(&mino) {
+            Some(mirror) => mirror,
+            None => return,
+        };
 
-            for (idx, (name, ino)) in mirror.by_name.iter().enumerate().skip(offset) {
-                if reply.add(*ino, (idx + 1) as i64, FileType::Symlink, name) {
-                    break;
-                }
-            }
+        mirror.update(&mut self.ino, &mut self.rec_by_ino);
+        if let Some(watcher) = &mut self.watcher {
+            watcher.watch(mino, mirror.watched.iter().map(|(path, _)| path.as_path()));
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      child()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn child(&self, parent: u64, name: &str) -> Option<FileAttr> {
+        if let Some(mirror) = self.by_ino.get(&parent) {
+            mirror.attr(*mirror.by_name.get(name)?)
         } else {
-            return reply.error(ENOENT);
+            let mirror = self.owner(parent)?;
+            mirror.attr(*mirror.dirs.get(&parent)?.get(name)?)
         }
+    }
 
-        reply.ok();
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      owner()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn owner(&self, ino: u64) -> Option<&Mirror> {
+        self.by_ino.get(self.rec_by_ino.get(&ino)?)
     }
 
 /*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
- *     │                                     readlink()                                     │     *
+ *     │                                       path()                                       │     *
 \*     └────────────────────────────────────────────────────────────────────────────────────┘     */
 
-    pub fn readlink(&self, ino: u64, reply: ReplyData) {
+    fn path(&self, ino: u64) -> Option<PathBuf> {
         if let Some(mino) = self.rec_by_ino.get(&ino) {
-            if let Some(Mirror { by_ino, .. }) = self.by_ino.get(mino) {
-                if let Some(path) = by_ino.get(&ino) {
-                    return reply.data(path.as_bytes());
-                }
+            match self.by_ino.get(mino) {
+                Some(mirror) if mirror.kind(ino) == FileType::Directory => {
+                    let path = mirror.by_ino.get(&ino)?;
+                    Some(PathBuf::from(OsStr::from_bytes(path.as_bytes())))
+                },
+                _ => None,
             }
+        } else {
+            self.passthrough.path(ino).map(Path::to_path_buf)
         }
-
-        reply.error(ENOENT);
     }
 
 /*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
@@ -210,6 +1139,8 @@ impl Mirrors {
                 padding: 0,
                 flags: 0,
             })
+        } else if let Some(mino) = self.rec_by_ino.get(&ino) {
+            self.by_ino.get(mino)?.attr(ino)
         } else {
             None
         }
@@ -222,11 +1153,24 @@ impl Mirrors {
 
 struct Mirror {
     ino: u64,
-    base: PathBuf,
-    renaming: Regex,
+    name: String,
+    bases: Bases,
+    renaming: Renaming,
+    passthrough: bool,
+    layout: Layout,
+    collided: Vec<String>,
     by_name: HashMap<String, u64>,
     by_ino: HashMap<u64, CString>,
-    modified: SystemTime,
+    dirs: HashMap<u64, BTreeMap<String, u64>>,
+    latest: HashSet<u64>,
+    files: HashSet<u64>,
+    stale: Option<Stale>,
+    stale_dir: Option<u64>,
+    pruneable: HashSet<u64>,
+    inos: HashMap<Vec<String>, u64>,
+    matched: Vec<PathBuf>,
+    watched: Vec<(PathBuf, Option<SystemTime>)>,
+    checked: SystemTime,
 }
 
 impl Mirror {
@@ -235,49 +1179,440 @@ impl Mirror {
 \*     └────────────────────────────────────────────────────────────────────────────────────┘     */
 
     fn update(&mut self, ino: &mut u64, rec_by_ino: &mut HashMap<u64, u64>) {
-        let modified = fs::metadata(&self.base).unwrap().modified().unwrap();
-        if modified == self.modified {
+        self.checked = SystemTime::now();
+
+        let bases = self.bases.expand();
+        let matched = bases.iter().map(|(_, base)| base.clone()).collect::<Vec<_>>();
+        let unchanged = !self.watched.is_empty()
+            && matched == self.matched
+            && self.watched.iter().all(|(path, mtime)| modified(path) == *mtime);
+
+        if unchanged {
             return;
         }
 
-        self.modified = modified;
-
-        let mut paths = fs::read_dir(&self.base)
-            .unwrap()
-            .map(Result::unwrap)
-            .filter(|entry| entry.file_type().unwrap().is_dir())
-            .map(|entry| entry.file_name())
-            .map(OsString::into_string)
-            .map(Result::unwrap)
-            .filter_map(|name| {
-                let renamed = self.renaming.captures(&name)?;
-                let renamed = renamed.get(renamed.len() - 1).unwrap();
-                let path = self.base.join(&name);
-                let path = CString::new(path.to_str().unwrap()).unwrap();
-                
-                Some((renamed.as_str().to_string(), path))
-            })
-            .collect::<HashMap<_, _>>();
+        self.matched = matched;
+        self.watched.clear();
 
-        let Mirror { ref mut by_name, ref mut by_ino, .. } = self;
-        by_name.retain(|name, ino| {
-            if let Some(path) = paths.remove(name) {
-                by_ino.insert(*ino, path);
-                true
-            } else {
-                by_ino.remove(ino);
-                rec_by_ino.remove(ino);
+        let mut renamed = BTreeMap::<_, BTreeMap<_, _>>::new();
+        for (levels, base) in bases {
+            let renamed = renamed.entry(levels).or_default();
+            self.scan(&base, self.bases.depth, renamed);
+        }
+
+        let stale = self.stale(&renamed);
+        for renamed in renamed.values_mut() {
+            self.bases.filter.newest(renamed);
+        }
+
+        for old in self.by_ino.keys().chain(self.dirs.keys()) {
+            rec_by_ino.remove(old);
+        }
 
-                false
+        self.by_name.clear();
+        self.by_ino.clear();
+        self.dirs.clear();
+        self.latest.clear();
+        self.files.clear();
+        self.pruneable.clear();
+        self.stale_dir = None;
+        self.collided.clear();
+
+        for (levels, renamed) in renamed.into_iter().filter(|(_, renamed)| !renamed.is_empty()) {
+            match &self.layout {
+                Layout::Collapsed(collisions) => {
+                    let collisions = *collisions;
+                    self.collapse(collisions, &levels, renamed, ino, rec_by_ino);
+                },
+                Layout::Versioned(versions) => {
+                    let versions = versions.clone();
+                    self.version(&versions, &levels, renamed, ino, rec_by_ino);
+                },
             }
-        });
+        }
+
+        if self.stale.is_some() {
+            // Superseded entries that the layout still shows (suffixed or versioned) aren't
+            // listed, deleting them from `.stale/` would remove something visible in the mirror.
+            let visible = self.by_ino.values().cloned().collect::<HashSet<_>>();
+            let stale = stale
+                .into_iter()
+                .filter(|(_, path, old)| *old || !visible.contains(path))
+                .map(|(original, path, _)| (original, path))
+                .collect();
+            self.prune(stale, ino, rec_by_ino);
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       scan()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn scan(&mut self, dir: &Path, depth: usize, renamed: &mut BTreeMap<String, Vec<Candidate>>) {
+        self.watched.push((dir.to_path_buf(), modified(dir)));
+
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(err) => return log::warn!("{}: {}", dir.display(), err),
+        };
+
+        for entry in entries.filter_map(Result::ok) {
+            let metadata = match entry.metadata() {
+                Ok(metadata) => metadata,
+                Err(_) => continue,
+            };
+
+            let name = match entry.file_name().into_string() {
+                Ok(name) if !self.bases.filter.excludes(&name) => name,
+                _ => continue,
+            };
+
+            // Excluded entries aren't descended into, entries that are merely filtered out are.
+            let renamed_to = self
+                .renaming
+                .rename(&name)
+                .filter(|_| self.bases.filter.matches(&name, &metadata));
+
+            match renamed_to {
+                Some(renaming) => {
+                    let capture = match &self.layout {
+                        Layout::Versioned(Versions { capture: Some(group), .. }) => {
+                            self.renaming.capture(&name, group)
+                        },
+                        _ => None,
+                    };
+
+                    renamed.entry(renaming).or_default().push(Candidate {
+                        path: CString::new(entry.path().as_os_str().as_bytes()).unwrap(),
+                        modified: metadata.modified().unwrap_or(UNIX_EPOCH),
+                        original: name,
+                        capture,
+                        dir: metadata.is_dir(),
+                    });
+                },
+                None if depth > 1 && metadata.is_dir() => {
+                    self.scan(&entry.path(), depth - 1, renamed)
+                },
+                None => (),
+            }
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      stale()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn stale(
+        &self,
+        renamed: &BTreeMap<Vec<String>, BTreeMap<String, Vec<Candidate>>>,
+    ) -> Vec<(String, CString, bool)> {
+        let policy = match &self.stale {
+            Some(policy) => policy,
+            None => return Vec::new(),
+        };
+
+        let mut stale = Vec::new();
+        for candidates in renamed.values().flat_map(BTreeMap::values) {
+            let newest = candidates.iter().map(|candidate| candidate.modified).max();
+            for candidate in candidates {
+                let old = policy.is_old(candidate);
+                if old || policy.is_superseded(candidate, newest.unwrap_or(UNIX_EPOCH)) {
+                    stale.push((candidate.original.clone(), candidate.path.clone(), old));
+                }
+            }
+        }
+
+        stale.sort_unstable_by(|(_, a, _), (_, b, _)| a.cmp(b));
+        stale
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      prune()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    // Entries are listed under their original names. The keys start with an empty segment, which
+    // neither levels nor renamed names can have, so that they don't clash with the others.
+    fn prune(
+        &mut self,
+        stale: Vec<(String, CString)>,
+        ino: &mut u64,
+        rec_by_ino: &mut HashMap<u64, u64>,
+    ) {
+        if self.by_name.contains_key(STALE) {
+            let bases = self.bases.patterns.join(", ");
+            log::error!("{}: an entry is named {}, not listing stale entries", bases, STALE);
+            return self.collided.push(STALE.into());
+        }
+
+        let dir = self.alloc(ino, vec![String::new()]);
+        rec_by_ino.insert(dir, self.ino);
+
+        let mut entries = BTreeMap::new();
+        for (original, path) in stale {
+            let mut name = original.clone();
+            let mut idx = 1;
+            while entries.contains_key(&name) {
+                idx += 1;
+                name = format!("{}~{}", original, idx);
+            }
+
+            let entry = self.alloc(ino, vec![String::new(), name.clone()]);
+            self.by_ino.insert(entry, path);
+            self.pruneable.insert(entry);
+            rec_by_ino.insert(entry, self.ino);
+            entries.insert(name, entry);
+        }
+
+        self.dirs.insert(dir, entries);
+        self.by_name.insert(STALE.into(), dir);
+        self.stale_dir = Some(dir);
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     collapse()                                     │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn collapse(
+        &mut self,
+        collisions: Collision,
+        levels: &[String],
+        renamed: BTreeMap<String, Vec<Candidate>>,
+        ino: &mut u64,
+        rec_by_ino: &mut HashMap<u64, u64>,
+    ) {
+        let parent = self.level(levels, ino, rec_by_ino);
+
+        for (name, candidate) in self.resolve(collisions, renamed) {
+            let mut key = levels.to_vec();
+            key.push(name.clone());
 
-        for (name, path) in paths {
+            let entry = self.alloc(ino, key);
+            if !candidate.dir {
+                self.files.insert(entry);
+            }
+
+            self.by_ino.insert(entry, candidate.path);
+            rec_by_ino.insert(entry, self.ino);
+            self.insert(parent, name, entry);
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     version()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn version(
+        &mut self,
+        versions: &Versions,
+        levels: &[String],
+        renamed: BTreeMap<String, Vec<Candidate>>,
+        ino: &mut u64,
+        rec_by_ino: &mut HashMap<u64, u64>,
+    ) {
+        let parent = self.level(levels, ino, rec_by_ino);
+
+        for (name, mut candidates) in renamed {
+            candidates.sort_unstable_by(|a, b| a.path.cmp(&b.path));
+
+            let mut key = levels.to_vec();
+            key.push(name.clone());
+
+            let dir = self.alloc(ino, key.clone());
+            rec_by_ino.insert(dir, self.ino);
+
+            let mut entries = BTreeMap::new();
+            for candidate in &candidates {
+                let version = versions.name(candidate);
+                let mut entry = version.clone();
+                let mut idx = 1;
+                while entry == LATEST || entries.contains_key(&entry) {
+                    idx += 1;
+                    entry = format!("{}~{}", version, idx);
+                }
+
+                let entry_ino = self.alloc(ino, [&key[..], &[entry.clone()]].concat());
+                if !candidate.dir {
+                    self.files.insert(entry_ino);
+                }
+
+                self.by_ino.insert(entry_ino, candidate.path.clone());
+                rec_by_ino.insert(entry_ino, self.ino);
+                entries.insert(entry, entry_ino);
+            }
+
+            if let Some(latest) = versions.latest(&candidates) {
+                let latest_ino = self.alloc(ino, [&key[..], &[LATEST.to_string()]].concat());
+                self.by_ino.insert(latest_ino, latest.path.clone());
+                self.latest.insert(latest_ino);
+                rec_by_ino.insert(latest_ino, self.ino);
+                entries.insert(LATEST.into(), latest_ino);
+            }
+
+            self.dirs.insert(dir, entries);
+            self.insert(parent, name, dir);
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      level()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    // Creates the directories for the wildcard segments of a base, returning the innermost one.
+    fn level(
+        &mut self,
+        levels: &[String],
+        ino: &mut u64,
+        rec_by_ino: &mut HashMap<u64, u64>,
+    ) -> Option<u64> {
+        let mut parent = None;
+        for (idx, level) in levels.iter().enumerate() {
+            let dir = self.alloc(ino, levels[..=idx].to_vec());
+            if let Entry::Vacant(entry) = self.dirs.entry(dir) {
+                entry.insert(BTreeMap::new());
+                rec_by_ino.insert(dir, self.ino);
+                self.insert(parent, level.clone(), dir);
+            }
+
+            parent = Some(dir);
+        }
+
+        parent
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      insert()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn insert(&mut self, parent: Option<u64>, name: String, ino: u64) {
+        match parent.and_then(|parent| self.dirs.get_mut(&parent)) {
+            Some(dir) => dir.insert(name, ino),
+            None => self.by_name.insert(name, ino),
+        };
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      alloc()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn alloc(&mut self, ino: &mut u64, key: Vec<String>) -> u64 {
+        if let Some(ino) = self.inos.get(&key) {
+            *ino
+        } else {
             *ino += 1;
+            self.inos.insert(key, *ino);
+            *ino
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     resolve()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn resolve(
+        &mut self,
+        collisions: Collision,
+        renamed: BTreeMap<String, Vec<Candidate>>,
+    ) -> HashMap<String, Candidate> {
+        let mut paths = HashMap::new();
+        let mut suffixed = Vec::new();
+
+        for (name, mut candidates) in renamed {
+            if candidates.len() == 1 {
+                paths.insert(name, candidates.pop().unwrap());
+                continue;
+            }
+
+            match collisions {
+                Collision::Newest => {
+                    let newest = candidates.into_iter().max_by_key(|candidate| candidate.modified);
+                    paths.insert(name, newest.unwrap());
+                },
+                Collision::Oldest => {
+                    let oldest = candidates.into_iter().min_by_key(|candidate| candidate.modified);
+                    paths.insert(name, oldest.unwrap());
+                },
+                Collision::Error => {
+                    let bases = self.bases.patterns.join(", ");
+                    log::error!("{}: {} entries rename to {}", bases, candidates.len(), name);
+                    self.collided.push(name);
+                },
+                Collision::Suffix => {
+                    candidates.sort_unstable_by(|a, b| a.path.cmp(&b.path));
+                    suffixed.push((name, candidates));
+                },
+            }
+        }
+
+        // Suffixes are assigned by path so that they stay stable across rescans, and skip names
+        // that are already taken by another entry.
+        for (name, candidates) in suffixed {
+            let mut idx = 1;
+            for candidate in candidates {
+                let mut suffixed = name.clone();
+                while paths.contains_key(&suffixed) {
+                    idx += 1;
+                    suffixed = format!("{}~{}", name, idx);
+                }
+
+                paths.insert(suffixed, candidate);
+            }
+        }
+
+        paths
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     entries()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn entries(&self) -> Vec<(Vec<&str>, &str, &CString)> {
+        let mut entries = Vec::new();
+        self.walk(Vec::new(), self.by_name.iter(), &mut entries);
+
+        entries
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       walk()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    // Versioned entries are reported through their `latest` link.
+    fn walk<'a>(
+        &'a self,
+        levels: Vec<&'a str>,
+        children: impl Iterator<Item = (&'a String, &'a u64)>,
+        entries: &mut Vec<(Vec<&'a str>, &'a str, &'a CString)>,
+    ) {
+        for (name, ino) in children.filter(|(_, ino)| self.stale_dir != Some(**ino)) {
+            if let Some(path) = self.by_ino.get(ino) {
+                entries.push((levels.clone(), name, path));
+            } else if let Some(children) = self.dirs.get(ino) {
+                let latest = children.get(LATEST).filter(|latest| self.latest.contains(latest));
+                match latest.and_then(|latest| self.by_ino.get(latest)) {
+                    Some(path) => entries.push((levels.clone(), name, path)),
+                    None => {
+                        let mut levels = levels.clone();
+                        levels.push(name);
+                        self.walk(levels, children.iter(), entries);
+                    },
+                }
+            }
+        }
+    }
 
-            self.by_name.insert(name, *ino).expect_none("mirror already contains path");
-            self.by_ino.insert(*ino, path);
-            rec_by_ino.insert(*ino, self.ino);
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       kind()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    // Only directories are passed through, files and symlinks are always linked to.
+    fn kind(&self, ino: u64) -> FileType {
+        let linked = [&self.latest, &self.files, &self.pruneable]
+            .iter()
+            .any(|inos| inos.contains(&ino));
+        if self.dirs.contains_key(&ino) || (self.passthrough && !linked) {
+            FileType::Directory
+        } else {
+            FileType::Symlink
         }
     }
 
@@ -285,8 +1620,32 @@ impl Mirror {
  *     │                                       attr()                                       │     *
 \*     └────────────────────────────────────────────────────────────────────────────────────┘     */
 
-    fn attr(&self, name: &str) -> Option<FileAttr> {
-        if let Some(ino) = self.by_name.get(name).copied() {
+    fn attr(&self, ino: u64) -> Option<FileAttr> {
+        if let Some(entries) = self.dirs.get(&ino) {
+            Some(FileAttr {
+                ino,
+                size: 0,
+                blocks: 0,
+                atime: UNIX_EPOCH,
+                mtime: UNIX_EPOCH,
+                ctime: UNIX_EPOCH,
+                crtime: UNIX_EPOCH,
+                kind: FileType::Directory,
+                perm: 0o555,
+                nlink: 1 + entries.len() as u32,
+                uid: 0,
+                gid: 0,
+                rdev: 0,
+                blksize: 0,
+                padding: 0,
+                flags: 0,
+            })
+        } else if let Some(path) = self.by_ino.get(&ino) {
+            if self.kind(ino) == FileType::Directory {
+                let metadata = fs::metadata(OsStr::from_bytes(path.as_bytes())).ok()?;
+                return Some(passthrough::attr(ino, &metadata));
+            }
+
             Some(FileAttr {
                 ino,
                 size: 0,
@@ -310,3 +1669,53 @@ impl Mirror {
         }
     }
 }
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                         Candidate                                          │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+struct Candidate {
+    path: CString,
+    modified: SystemTime,
+    original: String,
+    capture: Option<String>,
+    dir: bool,
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                       has_capture()                                        │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+fn has_capture(regex: &Regex, name: &str) -> bool {
+    match name.parse::<usize>() {
+        Ok(idx) => idx < regex.captures_len(),
+        Err(_) => regex.capture_names().flatten().any(|capture| capture == name),
+    }
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                       expand_home()                                        │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+pub fn expand_home(path: &str) -> String {
+    match (path.strip_prefix("~/"), std::env::var("HOME")) {
+        (Some(path), Ok(home)) => format!("{}/{}", home, path),
+        _ => path.to_string(),
+    }
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                         is_glob()                                          │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+fn is_glob(path: &str) -> bool {
+    path.contains(|chr| "*?[".contains(chr))
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                         modified()                                         │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
@@ -0,0 +1,732 @@
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                                                                            │ *
+ * │ This Source Code Form is subject to the terms of the Mozilla Public                        │ *
+ * │ License, v. 2.0. If a copy of the MPL was not distributed with this                        │ *
+ * │ file, You can obtain one at http://mozilla.org/MPL/2.0/.                                   │ *
+ * │                                                                                            │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                            use                                             │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+use super::{TTL, ROOT_INO};
+use super::passthrough::{self, errno};
+use super::projects::Projects;
+use fuser::{
+    FileAttr, FileType,
+    ReplyAttr, ReplyCreate, ReplyData, ReplyDirectory, ReplyEmpty, ReplyEntry, ReplyWrite,
+    TimeOrNow,
+};
+use libc::{EACCES, EINVAL, ENOENT, ENOTEMPTY, EXDEV};
+use std::collections::{BTreeMap, HashMap};
+use std::ffi::OsString;
+use std::fs::{self, OpenOptions};
+use std::io;
+use std::os::unix::fs::{DirBuilderExt, OpenOptionsExt};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                           const                                            │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+pub const INO: u64 = 1 << 60;
+pub const STR: &str = "overlay";
+
+const CONTROL_INO: u64 = INO + 1;
+const CONTROL_STR: &str = ".control";
+
+const WHITEOUT: &str = ".wh.";
+const OPAQUE: &str = ".wh..opq";
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                          Overlay                                           │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+pub struct Overlay {
+    ino: u64,
+    state: PathBuf,
+    by_path: HashMap<PathBuf, u64>,
+    by_ino: HashMap<u64, PathBuf>,
+}
+
+impl Overlay {
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       new()                                        │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn new(state: PathBuf) -> Self {
+        Overlay {
+            ino: CONTROL_INO,
+            state,
+            by_path: HashMap::new(),
+            by_ino: HashMap::new(),
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     inherit()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn inherit(&mut self, old: &mut Self) {
+        // Overlay inodes are keyed by path, so they stay valid whatever the new config says.
+        self.ino = self.ino.max(old.ino);
+        std::mem::swap(&mut self.by_path, &mut old.by_path);
+        std::mem::swap(&mut self.by_ino, &mut old.by_ino);
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     getattr()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn getattr(&self, projects: &Projects, ino: u64, reply: ReplyAttr) {
+        if let Some(attr) = self.attr(projects, ino) {
+            reply.attr(&TTL, &attr);
+        } else {
+            reply.error(ENOENT);
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      lookup()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn lookup(&mut self, projects: &Projects, parent: u64, name: &str, reply: ReplyEntry) {
+        let ino = if parent == ROOT_INO {
+            INO
+        } else if parent == INO && name == CONTROL_STR {
+            CONTROL_INO
+        } else if let Some(path) = self.join(projects, parent, name) {
+            if self.real(projects, &path).is_none() {
+                return reply.error(ENOENT);
+            }
+
+            self.add_path(path)
+        } else {
+            return reply.error(ENOENT);
+        };
+
+        match self.attr(projects, ino) {
+            Some(attr) => reply.entry(&TTL, &attr, 0),
+            None => reply.error(ENOENT),
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     readdir()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn readdir(&mut self, projects: &Projects, ino: u64, offset: usize, mut reply: ReplyDirectory) {
+        let entries = if ino == INO {
+            let mut entries = projects
+                .iter()
+                .map(|(name, _)| (name.to_string(), FileType::Directory))
+                .collect::<BTreeMap<_, _>>();
+            entries.insert(CONTROL_STR.into(), FileType::RegularFile);
+
+            entries
+        } else if let Some(path) = self.by_ino.get(&ino).cloned() {
+            match self.entries(projects, &path) {
+                Ok(entries) => entries,
+                Err(err) => return reply.error(errno(err)),
+            }
+        } else {
+            return reply.error(ENOENT);
+        };
+
+        for (idx, (name, kind)) in entries.into_iter().enumerate().skip(offset) {
+            let ino = match self.join(projects, ino, &name) {
+                Some(path) => self.add_path(path),
+                None => CONTROL_INO,
+            };
+
+            if reply.add(ino, (idx + 1) as i64, kind, name) {
+                break;
+            }
+        }
+
+        reply.ok();
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     readlink()                                     │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn readlink(&self, projects: &Projects, ino: u64, reply: ReplyData) {
+        match self.by_ino.get(&ino).and_then(|path| self.real(projects, path)) {
+            Some(path) => passthrough::readlink(&path, reply),
+            None => reply.error(ENOENT),
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       read()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn read(&self, projects: &Projects, ino: u64, offset: i64, size: u32, reply: ReplyData) {
+        if ino == CONTROL_INO {
+            let control = self.control(projects);
+            let start = (offset as usize).min(control.len());
+            let end = (start + size as usize).min(control.len());
+
+            return reply.data(&control.as_bytes()[start..end]);
+        }
+
+        match self.by_ino.get(&ino).and_then(|path| self.real(projects, path)) {
+            Some(path) => passthrough::read(&path, offset, size, reply),
+            None => reply.error(ENOENT),
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      write()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn write(&self, projects: &Projects, ino: u64, offset: i64, data: &[u8], reply: ReplyWrite) {
+        if ino == CONTROL_INO {
+            return match self.command(projects, data) {
+                Ok(()) => reply.written(data.len() as u32),
+                Err(err) => reply.error(err),
+            };
+        }
+
+        let path = match self.by_ino.get(&ino) {
+            Some(path) => path,
+            None => return reply.error(ENOENT),
+        };
+
+        match self.copy_up(projects, path) {
+            Ok(upper) => passthrough::write(&upper, offset, data, reply),
+            Err(err) => reply.error(errno(err)),
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     setattr()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn setattr(
+        &self,
+        projects: &Projects,
+        ino: u64,
+        mode: Option<u32>,
+        size: Option<u64>,
+        times: (Option<TimeOrNow>, Option<TimeOrNow>),
+        reply: ReplyAttr,
+    ) {
+        if ino == CONTROL_INO {
+            return self.getattr(projects, ino, reply);
+        }
+
+        let path = match self.by_ino.get(&ino) {
+            Some(path) => path,
+            None => return reply.error(ENOENT),
+        };
+
+        match self.copy_up(projects, path) {
+            Ok(upper) => passthrough::setattr(ino, &upper, mode, size, times, reply),
+            Err(err) => reply.error(errno(err)),
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      create()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn create(
+        &mut self,
+        projects: &Projects,
+        parent: u64,
+        name: &str,
+        mode: u32,
+        reply: ReplyCreate,
+    ) {
+        let path = match self.join(projects, parent, name) {
+            Some(path) => path,
+            None => return reply.error(EACCES),
+        };
+
+        let res = self.prepare(projects, &path).and_then(|upper| {
+            OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .mode(mode)
+                .open(upper)?
+                .metadata()
+        });
+
+        match res {
+            Ok(metadata) => {
+                let ino = self.add_path(path);
+                reply.created(&TTL, &passthrough::attr(ino, &metadata), 0, 0, 0);
+            },
+            Err(err) => reply.error(errno(err)),
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      mkdir()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn mkdir(&mut self, projects: &Projects, parent: u64, name: &str, mode: u32, reply: ReplyEntry) {
+        let path = match self.join(projects, parent, name) {
+            Some(path) => path,
+            None => return reply.error(EACCES),
+        };
+
+        // Like overlayfs, a directory recreated over a removed one is opaque, so that the project's
+        // contents don't show through it again.
+        let res = self.prepare(projects, &path).and_then(|upper| {
+            let lower = self.lower(projects, &path).map(fs::symlink_metadata);
+            let shadowed = matches!(lower, Some(Ok(_)));
+            fs::DirBuilder::new().mode(mode).create(&upper)?;
+            if shadowed {
+                fs::write(upper.join(OPAQUE), b"")?;
+            }
+
+            fs::symlink_metadata(upper)
+        });
+
+        match res {
+            Ok(metadata) => {
+                let ino = self.add_path(path);
+                reply.entry(&TTL, &passthrough::attr(ino, &metadata), 0);
+            },
+            Err(err) => reply.error(errno(err)),
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      unlink()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn unlink(&mut self, projects: &Projects, parent: u64, name: &str, reply: ReplyEmpty) {
+        let path = match self.join(projects, parent, name) {
+            Some(path) if parent != INO => path,
+            _ => return reply.error(EACCES),
+        };
+
+        match self.remove(projects, &path, fs::remove_file) {
+            Ok(()) => reply.ok(),
+            Err(err) => reply.error(errno(err)),
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      rmdir()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn rmdir(&mut self, projects: &Projects, parent: u64, name: &str, reply: ReplyEmpty) {
+        let path = match self.join(projects, parent, name) {
+            Some(path) if parent != INO => path,
+            _ => return reply.error(EACCES),
+        };
+
+        match self.entries(projects, &path) {
+            Ok(entries) if !entries.is_empty() => return reply.error(ENOTEMPTY),
+            Ok(_) => (),
+            Err(err) => return reply.error(errno(err)),
+        }
+
+        match self.remove(projects, &path, fs::remove_dir_all) {
+            Ok(()) => reply.ok(),
+            Err(err) => reply.error(errno(err)),
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      rename()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn rename(
+        &mut self,
+        projects: &Projects,
+        parent: u64,
+        name: &str,
+        new_parent: u64,
+        new_name: &str,
+        reply: ReplyEmpty,
+    ) {
+        let from = self.join(projects, parent, name);
+        let to = self.join(projects, new_parent, new_name);
+        let (from, to) = match (from, to) {
+            (Some(from), Some(to)) if parent != INO && new_parent != INO => (from, to),
+            _ => return reply.error(EACCES),
+        };
+
+        // Directories would need a recursive copy-up; `EXDEV` makes `mv` fall back to copying.
+        match self.real(projects, &from).map(fs::symlink_metadata) {
+            Some(Ok(metadata)) if metadata.is_dir() => return reply.error(EXDEV),
+            Some(Ok(_)) => (),
+            Some(Err(err)) => return reply.error(errno(err)),
+            None => return reply.error(ENOENT),
+        }
+
+        let res = self.copy_up(projects, &from).and_then(|upper| {
+            fs::rename(upper, self.prepare(projects, &to)?)?;
+            self.hide(projects, &from)
+        });
+
+        match res {
+            Ok(()) => {
+                if let Some(ino) = self.by_path.remove(&from) {
+                    self.by_ino.insert(ino, to.clone());
+                    self.by_path.insert(to, ino);
+                }
+
+                reply.ok();
+            },
+            Err(err) => reply.error(errno(err)),
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       join()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    // Aliases resolve to their project's name, so that they share its upper directory.
+    fn join(&self, projects: &Projects, parent: u64, name: &str) -> Option<PathBuf> {
+        if name.starts_with(WHITEOUT) {
+            None
+        } else if parent == INO {
+            Some(PathBuf::from(projects.name(name)?)).filter(|_| name != CONTROL_STR)
+        } else {
+            Some(self.by_ino.get(&parent)?.join(name))
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     add_path()                                     │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn add_path(&mut self, path: PathBuf) -> u64 {
+        if let Some(ino) = self.by_path.get(&path) {
+            return *ino;
+        }
+
+        self.ino += 1;
+
+        self.by_ino.insert(self.ino, path.clone());
+        self.by_path.insert(path, self.ino);
+
+        self.ino
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      lower()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn lower(&self, projects: &Projects, path: &Path) -> Option<PathBuf> {
+        // Nothing below an opaque directory comes from the project.
+        let opaque = path
+            .ancestors()
+            .any(|dir| fs::symlink_metadata(self.state.join(dir).join(OPAQUE)).is_ok());
+        if opaque {
+            return None;
+        }
+
+        let mut components = path.iter();
+        let project = projects.get(components.next()?.to_str()?)?;
+
+        Some(project.join(components.as_path()))
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     whiteout()                                     │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn whiteout(&self, path: &Path) -> PathBuf {
+        let mut name = OsString::from(WHITEOUT);
+        name.push(path.file_name().unwrap());
+
+        self.state.join(path).with_file_name(name)
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       real()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn real(&self, projects: &Projects, path: &Path) -> Option<PathBuf> {
+        let upper = self.state.join(path);
+        if fs::symlink_metadata(&upper).is_ok() {
+            return Some(upper);
+        } else if fs::symlink_metadata(self.whiteout(path)).is_ok() {
+            return None;
+        }
+
+        let lower = self.lower(projects, path)?;
+        if fs::symlink_metadata(&lower).is_ok() {
+            Some(lower)
+        } else {
+            None
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     entries()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn entries(&self, projects: &Projects, path: &Path) -> io::Result<BTreeMap<String, FileType>> {
+        let mut entries = BTreeMap::new();
+        let mut whiteouts = Vec::new();
+
+        if let Ok(upper) = fs::read_dir(self.state.join(path)) {
+            for entry in upper {
+                // Names that aren't valid UTF-8 can't be looked up through the overlay.
+                let entry = entry?;
+                let name = match entry.file_name().into_string() {
+                    Ok(name) => name,
+                    Err(_) => continue,
+                };
+
+                if name == OPAQUE {
+                    continue;
+                } else if let Some(name) = name.strip_prefix(WHITEOUT) {
+                    whiteouts.push(name.to_string());
+                } else {
+                    entries.insert(name, passthrough::kind(entry.file_type()?));
+                }
+            }
+        }
+
+        if let Some(lower) = self.lower(projects, path) {
+            if let Ok(lower) = fs::read_dir(lower) {
+                for entry in lower {
+                    let entry = entry?;
+                    let name = match entry.file_name().into_string() {
+                        Ok(name) => name,
+                        Err(_) => continue,
+                    };
+
+                    if !whiteouts.contains(&name) && !entries.contains_key(&name) {
+                        entries.insert(name, passthrough::kind(entry.file_type()?));
+                    }
+                }
+            }
+        }
+
+        Ok(entries)
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     prepare()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn prepare(&self, projects: &Projects, path: &Path) -> io::Result<PathBuf> {
+        if let Some(parent) = path.parent() {
+            if parent.as_os_str().is_empty() {
+                return Err(io::Error::from_raw_os_error(EACCES));
+            }
+
+            self.copy_up(projects, parent)?;
+        }
+
+        match fs::remove_file(self.whiteout(path)) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err),
+            _ => (),
+        }
+
+        Ok(self.state.join(path))
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     copy_up()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn copy_up(&self, projects: &Projects, path: &Path) -> io::Result<PathBuf> {
+        let upper = self.state.join(path);
+        if fs::symlink_metadata(&upper).is_ok() {
+            return Ok(upper);
+        }
+
+        let lower = self.real(projects, path).ok_or_else(|| io::Error::from_raw_os_error(ENOENT))?;
+        let metadata = fs::symlink_metadata(&lower)?;
+
+        if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            self.copy_up(projects, parent)?;
+        } else {
+            fs::create_dir_all(&self.state)?;
+        }
+
+        if metadata.is_dir() {
+            fs::create_dir(&upper)?;
+            fs::set_permissions(&upper, metadata.permissions())?;
+        } else if metadata.file_type().is_symlink() {
+            std::os::unix::fs::symlink(fs::read_link(&lower)?, &upper)?;
+        } else {
+            fs::copy(&lower, &upper)?;
+        }
+
+        Ok(upper)
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      remove()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn remove(
+        &mut self,
+        projects: &Projects,
+        path: &Path,
+        remove: fn(PathBuf) -> io::Result<()>,
+    ) -> io::Result<()> {
+        let upper = self.state.join(path);
+        if fs::symlink_metadata(&upper).is_ok() {
+            remove(upper)?;
+        } else if self.real(projects, path).is_none() {
+            return Err(io::Error::from_raw_os_error(ENOENT));
+        }
+
+        self.hide(projects, path)?;
+        if let Some(ino) = self.by_path.remove(path) {
+            self.by_ino.remove(&ino);
+        }
+
+        Ok(())
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       hide()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn hide(&self, projects: &Projects, path: &Path) -> io::Result<()> {
+        if let Some(lower) = self.lower(projects, path) {
+            if fs::symlink_metadata(lower).is_ok() {
+                self.copy_up(projects, path.parent().unwrap())?;
+                fs::write(self.whiteout(path), b"")?;
+            }
+        }
+
+        Ok(())
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     control()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn control(&self, projects: &Projects) -> String {
+        fn walk(base: &Path, dir: &Path, project: &str, control: &mut String) {
+            let mut entries = match fs::read_dir(base.join(dir)) {
+                Ok(entries) => entries.filter_map(Result::ok).collect::<Vec<_>>(),
+                Err(_) => return,
+            };
+            entries.sort_by_key(|entry| entry.file_name());
+
+            for entry in entries {
+                let name = match entry.file_name().into_string() {
+                    Ok(name) => name,
+                    Err(_) => continue,
+                };
+                let is_dir = entry.file_type().map(|kind| kind.is_dir()).unwrap_or(false);
+
+                // An opaque directory replaces the project's, so it's listed as deleted first.
+                if name == OPAQUE {
+                    control.push_str(&format!("{} D {}\n", project, dir.display()));
+                } else if let Some(name) = name.strip_prefix(WHITEOUT) {
+                    control.push_str(&format!("{} D {}\n", project, dir.join(name).display()));
+                } else if is_dir {
+                    walk(base, &dir.join(&name), project, control);
+                } else {
+                    control.push_str(&format!("{} M {}\n", project, dir.join(name).display()));
+                }
+            }
+        }
+
+        let mut names = projects.iter().map(|(name, _)| name).collect::<Vec<_>>();
+        names.sort_unstable();
+
+        let mut control = String::new();
+        for name in names {
+            walk(&self.state.join(name), Path::new(""), name, &mut control);
+        }
+
+        control
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     command()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn command(&self, projects: &Projects, data: &[u8]) -> Result<(), i32> {
+        let command = std::str::from_utf8(data).map_err(|_| EINVAL)?;
+        let mut words = command.split_whitespace();
+
+        match (words.next(), words.next(), words.next()) {
+            (Some("reset"), None, None) => {
+                for (name, _) in projects.iter() {
+                    self.reset(name)?;
+                }
+
+                Ok(())
+            },
+            (Some("reset"), Some(name), None) => match projects.name(name) {
+                Some(name) => self.reset(name),
+                None => Err(EINVAL),
+            },
+            _ => Err(EINVAL),
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      reset()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn reset(&self, name: &str) -> Result<(), i32> {
+        match fs::remove_dir_all(self.state.join(name)) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => Err(errno(err)),
+            _ => Ok(()),
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       attr()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn attr(&self, projects: &Projects, ino: u64) -> Option<FileAttr> {
+        if ino == INO {
+            Some(FileAttr {
+                ino,
+                size: 0,
+                blocks: 0,
+                atime: UNIX_EPOCH,
+                mtime: UNIX_EPOCH,
+                ctime: UNIX_EPOCH,
+                crtime: UNIX_EPOCH,
+                kind: FileType::Directory,
+                perm: 0o555,
+                nlink: 2,
+                uid: 0,
+                gid: 0,
+                rdev: 0,
+                blksize: 0,
+                padding: 0,
+                flags: 0,
+            })
+        } else if ino == CONTROL_INO {
+            Some(FileAttr {
+                ino,
+                size: self.control(projects).len() as u64,
+                blocks: 0,
+                atime: UNIX_EPOCH,
+                mtime: UNIX_EPOCH,
+                ctime: UNIX_EPOCH,
+                crtime: UNIX_EPOCH,
+                kind: FileType::RegularFile,
+                perm: 0o644,
+                nlink: 1,
+                uid: 0,
+                gid: 0,
+                rdev: 0,
+                blksize: 0,
+                padding: 0,
+                flags: 0,
+            })
+        } else {
+            let path = self.real(projects, self.by_ino.get(&ino)?)?;
+            let metadata = fs::symlink_metadata(path).ok()?;
+
+            Some(passthrough::attr(ino, &metadata))
+        }
+    }
+}
@@ -0,0 +1,463 @@
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                                                                            │ *
+ * │ This Source Code Form is subject to the terms of the Mozilla Public                        │ *
+ * │ License, v. 2.0. If a copy of the MPL was not distributed with this                        │ *
+ * │ file, You can obtain one at http://mozilla.org/MPL/2.0/.                                   │ *
+ * │                                                                                            │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                            use                                             │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+use super::TTL;
+use fuser::{
+    FileAttr, FileType,
+    ReplyAttr, ReplyCreate, ReplyData, ReplyDirectory, ReplyEmpty, ReplyEntry, ReplyWrite,
+    TimeOrNow,
+};
+use libc::{c_int, EIO};
+use std::collections::HashMap;
+use std::ffi::{CString, OsStr};
+use std::fs::{self, File, Metadata, OpenOptions, Permissions};
+use std::io;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::{DirBuilderExt, FileExt, FileTypeExt, MetadataExt, OpenOptionsExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, UNIX_EPOCH};
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                           const                                            │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+// What `libfuse` reports for entries whose inode isn't known yet.
+const UNKNOWN_INO: u64 = 0xffff_ffff;
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                        Passthrough                                         │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+// Inodes only live as long as the kernel holds references to them (see `forget()`), so that
+// browsing large trees doesn't grow the tables forever.
+pub struct Passthrough {
+    by_path: HashMap<PathBuf, u64>,
+    by_ino: HashMap<u64, PathBuf>,
+    lookups: HashMap<u64, u64>,
+}
+
+impl Passthrough {
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       new()                                        │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn new() -> Self {
+        Passthrough {
+            by_path: HashMap::new(),
+            by_ino: HashMap::new(),
+            lookups: HashMap::new(),
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       path()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn path(&self, ino: u64) -> Option<&Path> {
+        self.by_ino.get(&ino).map(PathBuf::as_path)
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      lookup()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn lookup(&mut self, ino: &mut u64, parent: &Path, name: &OsStr, reply: ReplyEntry) {
+        let path = parent.join(name);
+
+        match fs::symlink_metadata(&path) {
+            Ok(metadata) => {
+                let ino = self.ino(ino, path);
+                reply.entry(&TTL, &attr(ino, &metadata), 0);
+            },
+            Err(err) => reply.error(errno(err)),
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     readdir()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn readdir(&self, path: &Path, offset: usize, mut reply: ReplyDirectory) {
+        let entries = match fs::read_dir(path) {
+            Ok(entries) => entries,
+            Err(err) => return reply.error(errno(err)),
+        };
+
+        for (idx, entry) in entries.enumerate().skip(offset) {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(err) => return reply.error(errno(err)),
+            };
+
+            // Listing entries doesn't count as a lookup, the kernel ignores their inode anyway.
+            let kind = entry.file_type().map(kind).unwrap_or(FileType::RegularFile);
+            let name = entry.file_name();
+            let ino = self.by_path.get(&entry.path()).copied().unwrap_or(UNKNOWN_INO);
+
+            if reply.add(ino, (idx + 1) as i64, kind, name) {
+                break;
+            }
+        }
+
+        reply.ok();
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      create()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn create(
+        &mut self,
+        ino: &mut u64,
+        parent: &Path,
+        name: &OsStr,
+        mode: u32,
+        reply: ReplyCreate,
+    ) {
+        let path = parent.join(name);
+        let res = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .mode(mode)
+            .open(&path)
+            .and_then(|file| file.metadata());
+
+        match res {
+            Ok(metadata) => {
+                let ino = self.ino(ino, path);
+                reply.created(&TTL, &attr(ino, &metadata), 0, 0, 0);
+            },
+            Err(err) => reply.error(errno(err)),
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      mkdir()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn mkdir(&mut self, ino: &mut u64, parent: &Path, name: &OsStr, mode: u32, reply: ReplyEntry) {
+        let path = parent.join(name);
+        let res = fs::DirBuilder::new()
+            .mode(mode)
+            .create(&path)
+            .and_then(|()| fs::symlink_metadata(&path));
+
+        match res {
+            Ok(metadata) => {
+                let ino = self.ino(ino, path);
+                reply.entry(&TTL, &attr(ino, &metadata), 0);
+            },
+            Err(err) => reply.error(errno(err)),
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      unlink()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn unlink(&mut self, parent: &Path, name: &OsStr, reply: ReplyEmpty) {
+        let path = parent.join(name);
+
+        match fs::remove_file(&path) {
+            Ok(()) => {
+                self.remove(&path);
+                reply.ok();
+            },
+            Err(err) => reply.error(errno(err)),
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      rmdir()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn rmdir(&mut self, parent: &Path, name: &OsStr, reply: ReplyEmpty) {
+        let path = parent.join(name);
+
+        match fs::remove_dir(&path) {
+            Ok(()) => {
+                self.remove(&path);
+                reply.ok();
+            },
+            Err(err) => reply.error(errno(err)),
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      rename()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn rename(
+        &mut self,
+        parent: &Path,
+        name: &OsStr,
+        new_parent: &Path,
+        new_name: &OsStr,
+        reply: ReplyEmpty,
+    ) {
+        let from = parent.join(name);
+        let to = new_parent.join(new_name);
+
+        match fs::rename(&from, &to) {
+            Ok(()) => {
+                self.remove(&to);
+
+                // Everything the kernel knows about under a renamed directory moved along with it.
+                let moved = self
+                    .by_path
+                    .keys()
+                    .filter(|path| path.starts_with(&from))
+                    .cloned()
+                    .collect::<Vec<_>>();
+
+                for path in moved {
+                    let ino = self.by_path.remove(&path).unwrap();
+                    let path = match path.strip_prefix(&from) {
+                        Ok(rest) if !rest.as_os_str().is_empty() => to.join(rest),
+                        _ => to.clone(),
+                    };
+
+                    self.by_ino.insert(ino, path.clone());
+                    self.by_path.insert(path, ino);
+                }
+
+                reply.ok();
+            },
+            Err(err) => reply.error(errno(err)),
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      forget()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn forget(&mut self, ino: u64, nlookup: u64) {
+        let lookups = match self.lookups.get_mut(&ino) {
+            Some(lookups) => lookups,
+            None => return,
+        };
+
+        *lookups = lookups.saturating_sub(nlookup);
+        if *lookups == 0 {
+            self.lookups.remove(&ino);
+            if let Some(path) = self.by_ino.remove(&ino) {
+                self.by_path.remove(&path);
+            }
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       ino()                                        │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    // Every inode handed to the kernel through an entry counts as a lookup.
+    fn ino(&mut self, ino: &mut u64, path: PathBuf) -> u64 {
+        let ino = if let Some(ino) = self.by_path.get(&path) {
+            *ino
+        } else {
+            *ino += 1;
+
+            self.by_ino.insert(*ino, path.clone());
+            self.by_path.insert(path, *ino);
+
+            *ino
+        };
+
+        *self.lookups.entry(ino).or_default() += 1;
+
+        ino
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      remove()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn remove(&mut self, path: &Path) {
+        if let Some(ino) = self.by_path.remove(path) {
+            self.by_ino.remove(&ino);
+            self.lookups.remove(&ino);
+        }
+    }
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                         getattr()                                          │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+pub fn getattr(ino: u64, path: &Path, reply: ReplyAttr) {
+    match fs::symlink_metadata(path) {
+        Ok(metadata) => reply.attr(&TTL, &attr(ino, &metadata)),
+        Err(err) => reply.error(errno(err)),
+    }
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                         readlink()                                         │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+pub fn readlink(path: &Path, reply: ReplyData) {
+    match fs::read_link(path) {
+        Ok(target) => reply.data(target.as_os_str().as_bytes()),
+        Err(err) => reply.error(errno(err)),
+    }
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                           read()                                           │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+pub fn read(path: &Path, offset: i64, size: u32, reply: ReplyData) {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(err) => return reply.error(errno(err)),
+    };
+
+    let mut data = vec![0; size as usize];
+    match file.read_at(&mut data, offset as u64) {
+        Ok(read) => reply.data(&data[..read]),
+        Err(err) => reply.error(errno(err)),
+    }
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                          write()                                           │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+pub fn write(path: &Path, offset: i64, data: &[u8], reply: ReplyWrite) {
+    let file = match OpenOptions::new().write(true).open(path) {
+        Ok(file) => file,
+        Err(err) => return reply.error(errno(err)),
+    };
+
+    match file.write_all_at(data, offset as u64) {
+        Ok(()) => reply.written(data.len() as u32),
+        Err(err) => reply.error(errno(err)),
+    }
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                         setattr()                                          │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+pub fn setattr(
+    ino: u64,
+    path: &Path,
+    mode: Option<u32>,
+    size: Option<u64>,
+    (atime, mtime): (Option<TimeOrNow>, Option<TimeOrNow>),
+    reply: ReplyAttr,
+) {
+    let res = (|| {
+        if let Some(mode) = mode {
+            fs::set_permissions(path, Permissions::from_mode(mode))?;
+        }
+
+        if let Some(size) = size {
+            OpenOptions::new().write(true).open(path)?.set_len(size)?;
+        }
+
+        if atime.is_some() || mtime.is_some() {
+            set_times(path, atime, mtime)?;
+        }
+
+        fs::symlink_metadata(path)
+    })();
+
+    match res {
+        Ok(metadata) => reply.attr(&TTL, &attr(ino, &metadata)),
+        Err(err) => reply.error(errno(err)),
+    }
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                           attr()                                           │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+pub fn attr(ino: u64, metadata: &Metadata) -> FileAttr {
+    FileAttr {
+        ino,
+        size: metadata.size(),
+        blocks: metadata.blocks(),
+        atime: metadata.accessed().unwrap_or(UNIX_EPOCH),
+        mtime: metadata.modified().unwrap_or(UNIX_EPOCH),
+        ctime: UNIX_EPOCH + Duration::new(metadata.ctime() as u64, metadata.ctime_nsec() as u32),
+        crtime: metadata.created().unwrap_or(UNIX_EPOCH),
+        kind: kind(metadata.file_type()),
+        perm: (metadata.mode() & 0o7777) as u16,
+        nlink: metadata.nlink() as u32,
+        uid: metadata.uid(),
+        gid: metadata.gid(),
+        rdev: metadata.rdev() as u32,
+        blksize: metadata.blksize() as u32,
+        padding: 0,
+        flags: 0,
+    }
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                           kind()                                           │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+pub fn kind(kind: fs::FileType) -> FileType {
+    if kind.is_dir() {
+        FileType::Directory
+    } else if kind.is_symlink() {
+        FileType::Symlink
+    } else if kind.is_fifo() {
+        FileType::NamedPipe
+    } else if kind.is_socket() {
+        FileType::Socket
+    } else if kind.is_block_device() {
+        FileType::BlockDevice
+    } else if kind.is_char_device() {
+        FileType::CharDevice
+    } else {
+        FileType::RegularFile
+    }
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                        set_times()                                         │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+fn set_times(path: &Path, atime: Option<TimeOrNow>, mtime: Option<TimeOrNow>) -> io::Result<()> {
+    fn timespec(time: Option<TimeOrNow>) -> libc::timespec {
+        match time {
+            None => libc::timespec { tv_sec: 0, tv_nsec: libc::UTIME_OMIT },
+            Some(TimeOrNow::Now) => libc::timespec { tv_sec: 0, tv_nsec: libc::UTIME_NOW },
+            Some(TimeOrNow::SpecificTime(time)) => {
+                let time = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+                libc::timespec {
+                    tv_sec: time.as_secs() as libc::time_t,
+                    tv_nsec: time.subsec_nanos() as libc::c_long,
+                }
+            },
+        }
+    }
+
+    let path = CString::new(path.as_os_str().as_bytes())?;
+    let times = [timespec(atime), timespec(mtime)];
+
+    if unsafe { libc::utimensat(libc::AT_FDCWD, path.as_ptr(), times.as_ptr(), 0) } == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                          errno()                                           │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+pub fn errno(err: io::Error) -> c_int {
+    err.raw_os_error().unwrap_or(EIO)
+}
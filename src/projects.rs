@@ -11,10 +11,18 @@
 \* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
 
 use super::{TTL, ROOT_INO};
-use fuser::{FileAttr, FileType, ReplyAttr, ReplyData, ReplyDirectory, ReplyEntry};
-use libc::ENOENT;
+use super::passthrough::{self, Passthrough};
+use fuser::{
+    FileAttr, FileType,
+    ReplyAttr, ReplyCreate, ReplyData, ReplyDirectory, ReplyEmpty, ReplyEntry, ReplyWrite,
+    TimeOrNow,
+};
+use libc::{EACCES, ENOENT};
 use std::collections::HashMap;
-use std::ffi::CString;
+use std::ffi::{CString, OsStr};
+use std::fs;
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
 use std::time::UNIX_EPOCH;
 
 /* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
@@ -32,6 +40,8 @@ pub struct Projects {
     ino: u64,
     by_name: HashMap<String, u64>,
     by_ino: HashMap<u64, Project>,
+    previous: HashMap<String, u64>,
+    passthrough: Passthrough,
 }
 
 impl Projects {
@@ -44,9 +54,26 @@ impl Projects {
             ino: INO,
             by_name: HashMap::new(),
             by_ino: HashMap::new(),
+            previous: HashMap::new(),
+            passthrough: Passthrough::new(),
         }
     }
 
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     inherit()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn inherit(&mut self, old: &mut Self) {
+        // Projects that survive a reload keep their inode, and so does everything below them.
+        self.ino = self.ino.max(old.ino);
+        self.previous = old
+            .by_ino
+            .iter()
+            .map(|(&ino, project)| (project.name.clone(), ino))
+            .collect();
+        std::mem::swap(&mut self.passthrough, &mut old.passthrough);
+    }
+
 /*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
  *     │                                   add_project()                                    │     *
 \*     └────────────────────────────────────────────────────────────────────────────────────┘     */
@@ -56,19 +83,28 @@ impl Projects {
         name: String,
         path: String,
         aliases: impl Iterator<Item = String>,
+        passthrough: bool,
     ) -> u64 {
-        self.ino += 1;
+        let ino = match self.previous.remove(&name) {
+            Some(ino) => ino,
+            None => {
+                self.ino += 1;
+                self.ino
+            },
+        };
 
-        self.by_name.insert(name.clone(), self.ino).expect_none("project already exists");
-        self.by_ino.insert(self.ino, Project {
+        self.by_name.insert(name.clone(), ino).expect_none("project already exists");
+        self.by_ino.insert(ino, Project {
+            name: name.clone(),
             path: CString::new(path).unwrap(),
+            passthrough,
         });
 
         for alias in aliases {
-            self.add_alias(alias, self.ino);
+            self.add_alias(alias, ino);
         }
 
-        self.ino
+        ino
     }
 
 /*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
@@ -79,12 +115,57 @@ impl Projects {
         self.by_name.insert(name, ino).expect_none("project already exists");
     }
 
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       get()                                        │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn get(&self, name: &str) -> Option<&Path> {
+        Some(self.by_ino.get(self.by_name.get(name)?)?.path())
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       name()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn name(&self, name: &str) -> Option<&str> {
+        Some(&self.by_ino.get(self.by_name.get(name)?)?.name)
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     project()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    // The name of the project whose root is `ino`.
+    pub fn project(&self, ino: u64) -> Option<&str> {
+        Some(&self.by_ino.get(&ino)?.name)
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       iter()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Path)> {
+        self.by_ino.values().map(|project| (project.name.as_str(), project.path()))
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     entries()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn entries(&self) -> impl Iterator<Item = (&str, &Path)> {
+        self.by_name
+            .iter()
+            .filter_map(move |(name, ino)| Some((name.as_str(), self.by_ino.get(ino)?.path())))
+    }
+
 /*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
  *     │                                     getattr()                                      │     *
 \*     └────────────────────────────────────────────────────────────────────────────────────┘     */
 
     pub fn getattr(&self, ino: u64, reply: ReplyAttr) {
-        if let Some(attr) = self.attr(ino) {
+        if let Some(path) = self.passthrough.path(ino) {
+            passthrough::getattr(ino, path, reply);
+        } else if let Some(attr) = self.attr(ino) {
             reply.attr(&TTL, &attr);
         } else {
             reply.error(ENOENT);
@@ -95,29 +176,52 @@ impl Projects {
  *     │                                      lookup()                                      │     *
 \*     └────────────────────────────────────────────────────────────────────────────────────┘     */
 
-    pub fn lookup(&self, parent: u64, name: &str, reply: ReplyEntry) {
+    pub fn lookup(&mut self, parent: u64, name: &OsStr, reply: ReplyEntry) {
+        if let Some(path) = self.path(parent) {
+            return self.passthrough.lookup(&mut self.ino, &path, name, reply);
+        }
+
         let ino = if parent == ROOT_INO {
             INO
-        } else if let Some(ino) = self.by_name.get(name) {
+        } else if let Some(ino) = name.to_str().and_then(|name| self.by_name.get(name)) {
             *ino
         } else {
             return reply.error(ENOENT);
         };
 
-        reply.entry(&TTL, &self.attr(ino).unwrap(), 0);
+        match self.attr(ino) {
+            Some(attr) => reply.entry(&TTL, &attr, 0),
+            None => reply.error(ENOENT),
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      forget()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn forget(&mut self, ino: u64, nlookup: u64) {
+        self.passthrough.forget(ino, nlookup);
     }
 
 /*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
  *     │                                     readdir()                                      │     *
 \*     └────────────────────────────────────────────────────────────────────────────────────┘     */
 
-    pub fn readdir(&self, ino: u64, offset: usize, mut reply: ReplyDirectory) {
-        if ino != INO {
+    pub fn readdir(&mut self, ino: u64, offset: usize, mut reply: ReplyDirectory) {
+        if let Some(path) = self.path(ino) {
+            return self.passthrough.readdir(&path, offset, reply);
+        } else if ino != INO {
             return reply.error(ENOENT);
         }
 
         for (idx, (name, ino)) in self.by_name.iter().enumerate().skip(offset) {
-            if reply.add(*ino, (idx + 1) as i64, FileType::Symlink, name) {
+            let kind = if self.by_ino[ino].passthrough {
+                FileType::Directory
+            } else {
+                FileType::Symlink
+            };
+
+            if reply.add(*ino, (idx + 1) as i64, kind, name) {
                 break;
             }
         }
@@ -131,11 +235,131 @@ impl Projects {
 
     pub fn readlink(&self, ino: u64, reply: ReplyData) {
         match self.by_ino.get(&ino) {
+            Some(Project { passthrough: true, .. }) => reply.error(ENOENT),
             Some(Project { path, .. }) => reply.data(path.as_bytes()),
+            None => match self.passthrough.path(ino) {
+                Some(path) => passthrough::readlink(path, reply),
+                None => reply.error(ENOENT),
+            },
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       read()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn read(&self, ino: u64, offset: i64, size: u32, reply: ReplyData) {
+        match self.passthrough.path(ino) {
+            Some(path) => passthrough::read(path, offset, size, reply),
             None => reply.error(ENOENT),
         }
     }
 
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      write()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn write(&self, ino: u64, offset: i64, data: &[u8], reply: ReplyWrite) {
+        match self.passthrough.path(ino) {
+            Some(path) => passthrough::write(path, offset, data, reply),
+            None => reply.error(EACCES),
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     setattr()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn setattr(
+        &self,
+        ino: u64,
+        mode: Option<u32>,
+        size: Option<u64>,
+        times: (Option<TimeOrNow>, Option<TimeOrNow>),
+        reply: ReplyAttr,
+    ) {
+        match self.path(ino) {
+            Some(path) => passthrough::setattr(ino, &path, mode, size, times, reply),
+            None => reply.error(EACCES),
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      create()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn create(&mut self, parent: u64, name: &OsStr, mode: u32, reply: ReplyCreate) {
+        match self.path(parent) {
+            Some(path) => self.passthrough.create(&mut self.ino, &path, name, mode, reply),
+            None => reply.error(EACCES),
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      mkdir()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn mkdir(&mut self, parent: u64, name: &OsStr, mode: u32, reply: ReplyEntry) {
+        match self.path(parent) {
+            Some(path) => self.passthrough.mkdir(&mut self.ino, &path, name, mode, reply),
+            None => reply.error(EACCES),
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      unlink()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn unlink(&mut self, parent: u64, name: &OsStr, reply: ReplyEmpty) {
+        match self.path(parent) {
+            Some(path) => self.passthrough.unlink(&path, name, reply),
+            None => reply.error(EACCES),
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      rmdir()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn rmdir(&mut self, parent: u64, name: &OsStr, reply: ReplyEmpty) {
+        match self.path(parent) {
+            Some(path) => self.passthrough.rmdir(&path, name, reply),
+            None => reply.error(EACCES),
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      rename()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn rename(
+        &mut self,
+        parent: u64,
+        name: &OsStr,
+        new_parent: u64,
+        new_name: &OsStr,
+        reply: ReplyEmpty,
+    ) {
+        match (self.path(parent), self.path(new_parent)) {
+            (Some(path), Some(new_path)) => {
+                self.passthrough.rename(&path, name, &new_path, new_name, reply)
+            },
+            _ => reply.error(EACCES),
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       path()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn path(&self, ino: u64) -> Option<PathBuf> {
+        match self.by_ino.get(&ino) {
+            Some(project) if project.passthrough => Some(project.path().to_path_buf()),
+            Some(_) => None,
+            None => self.passthrough.path(ino).map(Path::to_path_buf),
+        }
+    }
+
 /*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
  *     │                                       attr()                                       │     *
 \*     └────────────────────────────────────────────────────────────────────────────────────┘     */
@@ -160,7 +384,12 @@ impl Projects {
                 padding: 0,
                 flags: 0,
             })
-        } else if self.by_ino.contains_key(&ino) {
+        } else if let Some(project) = self.by_ino.get(&ino) {
+            if project.passthrough {
+                let metadata = fs::metadata(project.path()).ok()?;
+                return Some(passthrough::attr(ino, &metadata));
+            }
+
             Some(FileAttr {
                 ino,
                 size: 0,
@@ -191,5 +420,17 @@ impl Projects {
 
 #[derive(Debug)]
 struct Project {
+    name: String,
     path: CString,
+    passthrough: bool,
+}
+
+impl Project {
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       path()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn path(&self) -> &Path {
+        Path::new(OsStr::from_bytes(self.path.as_bytes()))
+    }
 }
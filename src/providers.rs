@@ -0,0 +1,542 @@
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                                                                            │ *
+ * │ This Source Code Form is subject to the terms of the Mozilla Public                        │ *
+ * │ License, v. 2.0. If a copy of the MPL was not distributed with this                        │ *
+ * │ file, You can obtain one at http://mozilla.org/MPL/2.0/.                                   │ *
+ * │                                                                                            │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                            use                                             │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+use super::{TTL, ROOT_INO};
+use super::mirrors::expand_home;
+use fuser::{FileAttr, FileType, ReplyAttr, ReplyData, ReplyDirectory, ReplyEntry};
+use libc::ENOENT;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::{self, Read};
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::process::CommandExt;
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                           const                                            │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+pub const INO: u64 = 1 << 44;
+
+const ERROR: &str = ".error";
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                         Providers                                          │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+// Unlike the other namespaces, providers are mounted right under the root, `INO` is only used as
+// the parent of their directories.
+pub struct Providers {
+    ino: u64,
+    by_name: HashMap<(u64, String), u64>,
+    by_ino: HashMap<u64, Node>,
+    providers: HashMap<u64, Provider>,
+}
+
+impl Providers {
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       new()                                        │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn new() -> Self {
+        let mut by_ino = HashMap::new();
+        by_ino.insert(INO, Node::Dir(INO, BTreeMap::new()));
+
+        Providers {
+            ino: INO,
+            by_name: HashMap::new(),
+            by_ino,
+            providers: HashMap::new(),
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     inherit()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn inherit(&mut self, old: &Self) {
+        self.ino = self.ino.max(old.ino);
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                   add_provider()                                   │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn add_provider(
+        &mut self,
+        name: String,
+        command: String,
+        ttl: Duration,
+        timeout: Duration,
+    ) {
+        let ino = self.add_node(INO, &name, Node::Dir(0, BTreeMap::new()));
+        self.by_ino.insert(ino, Node::Dir(ino, BTreeMap::new()));
+        if let Some(Node::Dir(_, providers)) = self.by_ino.get_mut(&INO) {
+            providers.insert(name.clone(), ino);
+        }
+
+        self.providers.insert(ino, Provider {
+            name,
+            command,
+            ttl,
+            timeout,
+            fetched: UNIX_EPOCH,
+            listed: false,
+            pending: None,
+            error: None,
+        });
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       iter()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn iter(&self) -> impl Iterator<Item = (u64, &str)> {
+        self.children(INO).into_iter().flatten().map(|(name, ino)| (*ino, name.as_str()))
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     getattr()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn getattr(&self, ino: u64, reply: ReplyAttr) {
+        if let Some(attr) = self.attr(ino) {
+            reply.attr(&TTL, &attr);
+        } else {
+            reply.error(ENOENT);
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      lookup()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn lookup(&mut self, parent: u64, name: &str, reply: ReplyEntry) {
+        let parent = if parent == ROOT_INO {
+            INO
+        } else {
+            self.refresh(parent);
+            parent
+        };
+
+        match self.children(parent).and_then(|children| children.get(name)) {
+            Some(ino) => reply.entry(&TTL, &self.attr(*ino).unwrap(), 0),
+            None => reply.error(ENOENT),
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     readdir()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn readdir(&mut self, ino: u64, offset: usize, mut reply: ReplyDirectory) {
+        if offset == 0 {
+            self.refresh(ino);
+        }
+
+        let children = match self.children(ino) {
+            Some(children) => children,
+            None => return reply.error(ENOENT),
+        };
+
+        for (idx, (name, ino)) in children.iter().enumerate().skip(offset) {
+            if reply.add(*ino, (idx + 1) as i64, self.by_ino[ino].kind(), name) {
+                break;
+            }
+        }
+
+        reply.ok();
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     readlink()                                     │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn readlink(&self, ino: u64, reply: ReplyData) {
+        match self.by_ino.get(&ino) {
+            Some(Node::Link(target)) => reply.data(target.as_os_str().as_bytes()),
+            _ => reply.error(ENOENT),
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       read()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn read(&self, ino: u64, offset: i64, size: u32, reply: ReplyData) {
+        match self.by_ino.get(&ino) {
+            Some(Node::Error(provider)) => {
+                let data = self.error(*provider).into_bytes();
+                let start = (offset.max(0) as usize).min(data.len());
+                let end = start.saturating_add(size as usize).min(data.len());
+                reply.data(&data[start..end]);
+            },
+            _ => reply.error(ENOENT),
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     refresh()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    // Runs the command of the provider owning `ino` again once its results are older than its
+    // TTL. A failing provider keeps listing what it returned last, along with an `.error` file.
+    // Commands run on their own thread: only the first listing waits for them, later ones get the
+    // previous results until the new ones are in.
+    fn refresh(&mut self, ino: u64) {
+        let pino = match self.by_ino.get(&ino) {
+            Some(Node::Dir(pino, _)) if *pino != INO => *pino,
+            _ => return,
+        };
+
+        let provider = self.providers.get_mut(&pino).unwrap();
+        let expired = provider.fetched.elapsed().unwrap_or_default() >= provider.ttl;
+        if provider.pending.is_none() && expired {
+            let (sender, receiver) = mpsc::channel();
+            let (command, timeout) = (provider.command.clone(), provider.timeout);
+            thread::spawn(move || {
+                let _ = sender.send(run(&command, timeout).and_then(|output| parse(&output)));
+            });
+
+            provider.fetched = SystemTime::now();
+            provider.pending = Some(receiver);
+        }
+
+        let result = match &provider.pending {
+            Some(pending) if !provider.listed => {
+                pending.recv().map_err(|_| TryRecvError::Disconnected)
+            },
+            Some(pending) => pending.try_recv(),
+            None => return,
+        };
+
+        let result = match result {
+            Ok(result) => result,
+            Err(TryRecvError::Empty) => return,
+            Err(TryRecvError::Disconnected) => Err("the command's thread exited".into()),
+        };
+
+        provider.pending = None;
+        provider.listed = true;
+
+        match result {
+            Ok(entries) => {
+                provider.error = None;
+                let children = self.build(pino, pino, entries);
+                self.by_ino.insert(pino, Node::Dir(pino, children));
+            },
+            Err(err) => {
+                log::warn!("provider {} failed: {}", provider.name, err);
+                provider.error = Some(err);
+
+                let ino = self.add_node(pino, ERROR, Node::Error(pino));
+                if let Some(Node::Dir(_, children)) = self.by_ino.get_mut(&pino) {
+                    children.insert(ERROR.to_string(), ino);
+                }
+            },
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      build()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn build(&mut self, pino: u64, parent: u64, entries: Vec<Entry>) -> BTreeMap<String, u64> {
+        let mut children = BTreeMap::new();
+
+        for entry in entries {
+            let ino = if entry.is_dir() {
+                let ino = self.add_node(parent, &entry.name, Node::Dir(pino, BTreeMap::new()));
+                let grandchildren = self.build(pino, ino, entry.children);
+                self.by_ino.insert(ino, Node::Dir(pino, grandchildren));
+                ino
+            } else {
+                let target = expand_home(&entry.target.unwrap());
+                self.add_node(parent, &entry.name, Node::Link(PathBuf::from(target)))
+            };
+
+            children.insert(entry.name, ino);
+        }
+
+        children
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     children()                                     │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn children(&self, ino: u64) -> Option<&BTreeMap<String, u64>> {
+        match self.by_ino.get(&ino) {
+            Some(Node::Dir(_, children)) => Some(children),
+            _ => None,
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      error()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn error(&self, pino: u64) -> String {
+        match self.providers.get(&pino).and_then(|provider| provider.error.as_ref()) {
+            Some(err) => format!("{}\n", err),
+            None => String::new(),
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     add_node()                                     │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn add_node(&mut self, parent: u64, name: &str, node: Node) -> u64 {
+        let ino = if let Some(ino) = self.by_name.get(&(parent, name.to_string())) {
+            *ino
+        } else {
+            self.ino += 1;
+            self.by_name.insert((parent, name.to_string()), self.ino);
+            self.ino
+        };
+
+        self.by_ino.insert(ino, node);
+
+        ino
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       attr()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn attr(&self, ino: u64) -> Option<FileAttr> {
+        let (kind, size, perm) = match self.by_ino.get(&ino)? {
+            Node::Dir(..) => (FileType::Directory, 0, 0o555),
+            Node::Link(target) => (FileType::Symlink, target.as_os_str().len() as u64, 0o555),
+            Node::Error(pino) => (FileType::RegularFile, self.error(*pino).len() as u64, 0o444),
+        };
+
+        Some(FileAttr {
+            ino,
+            size,
+            blocks: 0,
+            atime: UNIX_EPOCH,
+            mtime: UNIX_EPOCH,
+            ctime: UNIX_EPOCH,
+            crtime: UNIX_EPOCH,
+            kind,
+            perm,
+            nlink: if kind == FileType::Directory { 2 } else { 1 },
+            uid: 0,
+            gid: 0,
+            rdev: 0,
+            blksize: 0,
+            padding: 0,
+            flags: 0,
+        })
+    }
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                          Provider                                          │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+struct Provider {
+    name: String,
+    command: String,
+    ttl: Duration,
+    timeout: Duration,
+    fetched: SystemTime,
+    listed: bool,
+    pending: Option<Receiver<Result<Vec<Entry>, String>>>,
+    error: Option<String>,
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                            Node                                            │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+// Directories know which provider they belong to, so that lookups can refresh it.
+enum Node {
+    Dir(u64, BTreeMap<String, u64>),
+    Link(PathBuf),
+    Error(u64),
+}
+
+impl Node {
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       kind()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn kind(&self) -> FileType {
+        match self {
+            Node::Dir(..) => FileType::Directory,
+            Node::Link(_) => FileType::Symlink,
+            Node::Error(_) => FileType::RegularFile,
+        }
+    }
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                           Entry                                            │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+// What providers print, e.g. `[{"name": "prod", "target": "~/k8s/prod"}]`.
+#[derive(Deserialize)]
+struct Entry {
+    name: String,
+    target: Option<String>,
+    kind: Option<Kind>,
+    #[serde(default)]
+    children: Vec<Entry>,
+}
+
+impl Entry {
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      is_dir()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn is_dir(&self) -> bool {
+        match self.kind {
+            Some(kind) => kind == Kind::Dir,
+            None => self.target.is_none(),
+        }
+    }
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                            Kind                                            │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+#[derive(Clone, Copy, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+enum Kind {
+    Dir,
+    Link,
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                           run()                                            │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+// Runs `command` through `sh`, killing it and everything it started if it doesn't exit and close
+// its output within `timeout`. The command gets its own process group for that, since whatever it
+// leaves running in the background can keep the pipes open.
+fn run(command: &str, timeout: Duration) -> Result<Vec<u8>, String> {
+    let mut child = unsafe {
+        Command::new("sh")
+            .arg("-c")
+            .arg(command)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .pre_exec(|| match libc::setpgid(0, 0) {
+                0 => Ok(()),
+                _ => Err(io::Error::last_os_error()),
+            })
+            .spawn()
+            .map_err(|err| format!("failed to run `{}`: {}", command, err))?
+    };
+
+    // Both pipes are drained while waiting, so that the command can't block on a full one.
+    let mut stdout = child.stdout.take().unwrap();
+    let mut stderr = child.stderr.take().unwrap();
+    let (stdout_sender, stdout_receiver) = mpsc::channel();
+    let (stderr_sender, stderr_receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let mut output = Vec::new();
+        let _ = stdout_sender.send(stdout.read_to_end(&mut output).map(|_| output));
+    });
+    thread::spawn(move || {
+        let mut output = String::new();
+        let _ = stderr.read_to_string(&mut output);
+        let _ = stderr_sender.send(output);
+    });
+
+    let timed_out = |child: &mut Child| {
+        kill(child);
+        Err(format!("`{}` timed out after {:?}", command, timeout))
+    };
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if Instant::now() < deadline => thread::sleep(Duration::from_millis(10)),
+            Ok(None) => return timed_out(&mut child),
+            Err(err) => {
+                kill(&mut child);
+                return Err(format!("failed to wait for `{}`: {}", command, err));
+            },
+        }
+    };
+
+    let remaining = || deadline.saturating_duration_since(Instant::now());
+    let stdout = match stdout_receiver.recv_timeout(remaining()) {
+        Ok(stdout) => stdout,
+        Err(_) => return timed_out(&mut child),
+    };
+    let stderr = stderr_receiver.recv_timeout(remaining()).unwrap_or_default();
+    if !status.success() {
+        return Err(format!("`{}` failed ({}): {}", command, status, stderr.trim()));
+    }
+
+    stdout.map_err(|err| format!("failed to read the output of `{}`: {}", command, err))
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                           kill()                                           │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+fn kill(child: &mut Child) {
+    unsafe { libc::kill(-(child.id() as libc::pid_t), libc::SIGKILL) };
+    let _ = child.wait();
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                          parse()                                           │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+fn parse(output: &[u8]) -> Result<Vec<Entry>, String> {
+    let entries = serde_json::from_slice::<Vec<Entry>>(output)
+        .map_err(|err| format!("invalid output: {}", err))?;
+
+    check(&entries, "")?;
+
+    Ok(entries)
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                          check()                                           │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+// Entries are checked before any of them gets mounted, so that invalid output doesn't replace the
+// previous results.
+fn check(entries: &[Entry], parent: &str) -> Result<(), String> {
+    let mut names = HashSet::new();
+    for entry in entries {
+        let path = format!("{}{}", parent, entry.name);
+        if matches!(entry.name.as_str(), "" | "." | ".." | ERROR) || entry.name.contains('/') {
+            return Err(format!("invalid entry name: {:?}", path));
+        } else if !names.insert(&entry.name) {
+            return Err(format!("{}: listed more than once", path));
+        } else if !entry.is_dir() && entry.target.is_none() {
+            return Err(format!("{}: links require a target", path));
+        } else if !entry.is_dir() && !entry.children.is_empty() {
+            return Err(format!("{}: only directories can have children", path));
+        }
+
+        check(&entry.children, &format!("{}/", path))?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,342 @@
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                                                                            │ *
+ * │ This Source Code Form is subject to the terms of the Mozilla Public                        │ *
+ * │ License, v. 2.0. If a copy of the MPL was not distributed with this                        │ *
+ * │ file, You can obtain one at http://mozilla.org/MPL/2.0/.                                   │ *
+ * │                                                                                            │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                            use                                             │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+use super::{TTL, ROOT_INO};
+use super::projects::Projects;
+use fuser::{FileAttr, FileType, ReplyAttr, ReplyData, ReplyDirectory, ReplyEntry};
+use libc::ENOENT;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::ffi::CString;
+use std::fs;
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                           const                                            │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+pub const INO: u64 = 1 << 59;
+pub const STR: &str = "recent";
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                          Activity                                          │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+#[derive(Clone, Copy, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Activity {
+    Mtime,
+    Newest,
+    Git,
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                           Recent                                           │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+pub struct Recent {
+    ino: u64,
+    count: usize,
+    activity: Activity,
+    scan: usize,
+    interval: Duration,
+    by_name: HashMap<String, u64>,
+    by_ino: HashMap<u64, Entry>,
+    entries: Vec<u64>,
+    updated: SystemTime,
+}
+
+impl Recent {
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       new()                                        │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn new() -> Self {
+        Recent {
+            ino: INO,
+            count: 10,
+            activity: Activity::Mtime,
+            scan: 10_000,
+            interval: Duration::from_secs(10),
+            by_name: HashMap::new(),
+            by_ino: HashMap::new(),
+            entries: Vec::new(),
+            updated: UNIX_EPOCH,
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     inherit()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn inherit(&mut self, old: &Self) {
+        self.ino = self.ino.max(old.ino);
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                    configure()                                     │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn configure(&mut self, count: usize, activity: Activity, scan: usize, interval: Duration) {
+        self.count = count;
+        self.activity = activity;
+        self.scan = scan;
+        self.interval = interval;
+        self.updated = UNIX_EPOCH;
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      index()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn index(&self) -> Vec<&str> {
+        self.entries.iter().filter_map(|ino| Some(self.by_ino.get(ino)?.name.as_str())).collect()
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     getattr()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn getattr(&self, ino: u64, reply: ReplyAttr) {
+        if let Some(attr) = self.attr(ino) {
+            reply.attr(&TTL, &attr);
+        } else {
+            reply.error(ENOENT);
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      lookup()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn lookup(&mut self, projects: &Projects, parent: u64, name: &str, reply: ReplyEntry) {
+        let ino = if parent == ROOT_INO {
+            INO
+        } else if parent == INO {
+            self.update(projects);
+
+            match self.by_name.get(name) {
+                Some(ino) if self.entries.contains(ino) => *ino,
+                _ => return reply.error(ENOENT),
+            }
+        } else {
+            return reply.error(ENOENT);
+        };
+
+        reply.entry(&TTL, &self.attr(ino).unwrap(), 0);
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     readdir()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn readdir(&mut self, projects: &Projects, ino: u64, offset: usize, mut reply: ReplyDirectory) {
+        if ino != INO {
+            return reply.error(ENOENT);
+        } else if offset == 0 {
+            self.update(projects);
+        }
+
+        for (idx, ino) in self.entries.iter().enumerate().skip(offset) {
+            if reply.add(*ino, (idx + 1) as i64, FileType::Symlink, &self.by_ino[ino].name) {
+                break;
+            }
+        }
+
+        reply.ok();
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     readlink()                                     │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn readlink(&self, ino: u64, reply: ReplyData) {
+        match self.by_ino.get(&ino) {
+            Some(Entry { path, .. }) => reply.data(path.as_bytes()),
+            None => reply.error(ENOENT),
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      update()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn update(&mut self, projects: &Projects) {
+        if self.updated.elapsed().unwrap_or_default() < self.interval {
+            return;
+        }
+
+        self.updated = SystemTime::now();
+
+        let mut activities = projects
+            .iter()
+            .map(|(name, path)| (name, path, self.activity(path)))
+            .collect::<Vec<_>>();
+        activities.sort_by(|(_, _, a), (_, _, b)| b.cmp(a));
+
+        self.entries.clear();
+        for (name, path, activity) in activities.into_iter().take(self.count) {
+            let ino = if let Some(ino) = self.by_name.get(name) {
+                *ino
+            } else {
+                self.ino += 1;
+                self.by_name.insert(name.to_string(), self.ino);
+                self.ino
+            };
+
+            self.by_ino.insert(ino, Entry {
+                name: name.to_string(),
+                path: CString::new(path.as_os_str().as_bytes()).unwrap(),
+                activity,
+            });
+            self.entries.push(ino);
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     activity()                                     │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn activity(&self, path: &Path) -> SystemTime {
+        let modified = fs::metadata(path)
+            .and_then(|metadata| metadata.modified())
+            .unwrap_or(UNIX_EPOCH);
+
+        match self.activity {
+            Activity::Mtime => modified,
+            Activity::Newest => newest(path, self.scan).max(modified),
+            Activity::Git => reflog(path).unwrap_or(modified),
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       attr()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn attr(&self, ino: u64) -> Option<FileAttr> {
+        if ino == INO {
+            Some(FileAttr {
+                ino,
+                size: 0,
+                blocks: 0,
+                atime: UNIX_EPOCH,
+                mtime: UNIX_EPOCH,
+                ctime: UNIX_EPOCH,
+                crtime: UNIX_EPOCH,
+                kind: FileType::Directory,
+                perm: 0o555,
+                nlink: 1 + self.entries.len() as u32,
+                uid: 0,
+                gid: 0,
+                rdev: 0,
+                blksize: 0,
+                padding: 0,
+                flags: 0,
+            })
+        } else {
+            let entry = self.by_ino.get(&ino)?;
+
+            Some(FileAttr {
+                ino,
+                size: 0,
+                blocks: 0,
+                atime: entry.activity,
+                mtime: entry.activity,
+                ctime: entry.activity,
+                crtime: UNIX_EPOCH,
+                kind: FileType::Symlink,
+                perm: 0o555,
+                nlink: 1,
+                uid: 0,
+                gid: 0,
+                rdev: 0,
+                blksize: 0,
+                padding: 0,
+                flags: 0,
+            })
+        }
+    }
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                           Entry                                            │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+struct Entry {
+    name: String,
+    path: CString,
+    activity: SystemTime,
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                          newest()                                          │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+fn newest(path: &Path, scan: usize) -> SystemTime {
+    let mut newest = UNIX_EPOCH;
+    let mut queue = VecDeque::new();
+    queue.push_back(path.to_path_buf());
+
+    let mut scanned = 0;
+    while let Some(dir) = queue.pop_front() {
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(_) => continue,
+        };
+
+        for entry in entries.filter_map(Result::ok) {
+            scanned += 1;
+            if scanned > scan {
+                return newest;
+            }
+
+            let metadata = match entry.metadata() {
+                Ok(metadata) => metadata,
+                Err(_) => continue,
+            };
+
+            if let Ok(modified) = metadata.modified() {
+                newest = newest.max(modified);
+            }
+
+            if metadata.is_dir() && entry.file_name() != ".git" && entry.file_name() != "target" {
+                queue.push_back(entry.path());
+            }
+        }
+    }
+
+    newest
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                          reflog()                                          │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+fn reflog(path: &Path) -> Option<SystemTime> {
+    let mut git = path.join(".git");
+    if git.is_file() {
+        let gitdir = fs::read_to_string(&git).ok()?;
+        git = path.join(gitdir.strip_prefix("gitdir:")?.trim());
+    }
+
+    let reflog = fs::read_to_string(git.join("logs/HEAD")).ok()?;
+    let line = reflog.lines().last()?;
+    let (entry, _) = line.split_once('\t').unwrap_or((line, ""));
+
+    let mut words = entry.rsplit(' ');
+    let _tz = words.next()?;
+    let time = words.next()?.parse().ok()?;
+
+    Some(UNIX_EPOCH + Duration::from_secs(time))
+}
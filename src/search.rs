@@ -0,0 +1,340 @@
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                                                                            │ *
+ * │ This Source Code Form is subject to the terms of the Mozilla Public                        │ *
+ * │ License, v. 2.0. If a copy of the MPL was not distributed with this                        │ *
+ * │ file, You can obtain one at http://mozilla.org/MPL/2.0/.                                   │ *
+ * │                                                                                            │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                            use                                             │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+use super::{TTL, ROOT_INO};
+use super::mirrors::Mirrors;
+use super::projects::Projects;
+use fuser::{FileAttr, FileType, ReplyAttr, ReplyData, ReplyDirectory, ReplyEntry};
+use libc::{EINVAL, ENOENT};
+use regex::Regex;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::ffi::CString;
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                           const                                            │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+pub const INO: u64 = 1 << 52;
+pub const STR: &str = "search";
+
+// Queries that haven't been used for this long are dropped, so that every name ever looked up
+// doesn't stay listed. It's well above the kernel's TTL, so a shell sitting in a query keeps it.
+const EXPIRY: Duration = Duration::from_secs(10 * 60);
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                           Search                                           │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+pub struct Search {
+    ino: u64,
+    paths: bool,
+    by_name: HashMap<String, u64>,
+    by_ino: HashMap<u64, Query>,
+    entries: HashMap<(u64, String), u64>,
+    rec_by_ino: HashMap<u64, u64>,
+}
+
+impl Search {
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       new()                                        │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn new() -> Self {
+        Search {
+            ino: INO,
+            paths: false,
+            by_name: HashMap::new(),
+            by_ino: HashMap::new(),
+            entries: HashMap::new(),
+            rec_by_ino: HashMap::new(),
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     inherit()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn inherit(&mut self, old: &Self) {
+        self.ino = self.ino.max(old.ino);
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                    configure()                                     │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn configure(&mut self, paths: bool) {
+        self.paths = paths;
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     getattr()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn getattr(&self, ino: u64, reply: ReplyAttr) {
+        if let Some(attr) = self.attr(ino) {
+            reply.attr(&TTL, &attr);
+        } else {
+            reply.error(ENOENT);
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      lookup()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn lookup(
+        &mut self,
+        projects: &Projects,
+        mirrors: &mut Mirrors,
+        parent: u64,
+        name: &str,
+        reply: ReplyEntry,
+    ) {
+        let ino = if parent == ROOT_INO {
+            INO
+        } else if parent == INO {
+            self.evict();
+
+            let regex = match Regex::new(name) {
+                Ok(regex) => regex,
+                Err(_) => return reply.error(EINVAL),
+            };
+
+            let ino = self.add_query(name, regex);
+            self.update(projects, mirrors, ino);
+
+            ino
+        } else if let Some(query) = self.by_ino.get_mut(&parent) {
+            query.used = SystemTime::now();
+            match query.by_name.get(name) {
+                Some((ino, _)) => *ino,
+                None => return reply.error(ENOENT),
+            }
+        } else {
+            return reply.error(ENOENT);
+        };
+
+        reply.entry(&TTL, &self.attr(ino).unwrap(), 0);
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     readdir()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn readdir(
+        &mut self,
+        projects: &Projects,
+        mirrors: &mut Mirrors,
+        ino: u64,
+        offset: usize,
+        mut reply: ReplyDirectory,
+    ) {
+        if ino == INO {
+            if offset == 0 {
+                self.evict();
+            }
+
+            let mut queries = self.by_name.iter().collect::<Vec<_>>();
+            queries.sort_unstable();
+
+            for (idx, (name, ino)) in queries.into_iter().enumerate().skip(offset) {
+                if reply.add(*ino, (idx + 1) as i64, FileType::Directory, name) {
+                    break;
+                }
+            }
+        } else if self.by_ino.contains_key(&ino) {
+            if offset == 0 {
+                self.update(projects, mirrors, ino);
+            }
+
+            let query = &self.by_ino[&ino];
+            for (idx, (name, (ino, _))) in query.by_name.iter().enumerate().skip(offset) {
+                if reply.add(*ino, (idx + 1) as i64, FileType::Symlink, name) {
+                    break;
+                }
+            }
+        } else {
+            return reply.error(ENOENT);
+        }
+
+        reply.ok();
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     readlink()                                     │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn readlink(&self, ino: u64, reply: ReplyData) {
+        let path = self.rec_by_ino
+            .get(&ino)
+            .and_then(|query| self.by_ino.get(query))
+            .and_then(|query| query.by_name.values().find(|(entry, _)| *entry == ino));
+
+        match path {
+            Some((_, path)) => reply.data(path.as_bytes()),
+            None => reply.error(ENOENT),
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                    add_query()                                     │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn add_query(&mut self, name: &str, regex: Regex) -> u64 {
+        if let Some(ino) = self.by_name.get(name) {
+            return *ino;
+        }
+
+        self.ino += 1;
+        self.by_name.insert(name.to_string(), self.ino);
+        self.by_ino.insert(self.ino, Query {
+            regex,
+            by_name: BTreeMap::new(),
+            updated: UNIX_EPOCH,
+            used: SystemTime::now(),
+        });
+
+        self.ino
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      evict()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn evict(&mut self) {
+        let expired = self.by_ino
+            .iter()
+            .filter(|(_, query)| query.used.elapsed().unwrap_or_default() > EXPIRY)
+            .map(|(ino, _)| *ino)
+            .collect::<HashSet<_>>();
+
+        if expired.is_empty() {
+            return;
+        }
+
+        self.by_ino.retain(|ino, _| !expired.contains(ino));
+        self.by_name.retain(|_, ino| !expired.contains(ino));
+        self.entries.retain(|(query, _), _| !expired.contains(query));
+        self.rec_by_ino.retain(|_, query| !expired.contains(query));
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      update()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn update(&mut self, projects: &Projects, mirrors: &mut Mirrors, ino: u64) {
+        let query = self.by_ino.get_mut(&ino).unwrap();
+        query.used = SystemTime::now();
+        if query.updated.elapsed().unwrap_or_default() < TTL {
+            return;
+        }
+
+        query.updated = SystemTime::now();
+        query.by_name.clear();
+
+        let paths = self.paths;
+        let matches = |name: &str, path: &Path| {
+            query.regex.is_match(name) || (paths && query.regex.is_match(&path.to_string_lossy()))
+        };
+
+        let mut found = BTreeMap::new();
+        for (name, path) in projects.entries().filter(|(name, path)| matches(name, path)) {
+            found.insert(name.to_string(), CString::new(path.as_os_str().as_bytes()).unwrap());
+        }
+
+        // Mirror entries named like a project, or like an entry of another mirror, are listed as
+        // `name@mirror`.
+        let mut matched = mirrors
+            .entries()
+            .filter(|(_, name, path)| matches(name, path))
+            .collect::<Vec<_>>();
+        matched.sort_unstable();
+
+        let mut counts = HashMap::<_, usize>::new();
+        for (_, name, _) in &matched {
+            *counts.entry(*name).or_default() += 1;
+        }
+
+        let taken = found.keys().cloned().collect::<HashSet<_>>();
+        for (mirror, name, path) in matched {
+            let name = if counts[name] > 1 || taken.contains(name) {
+                format!("{}@{}", name, mirror)
+            } else {
+                name.to_string()
+            };
+
+            found.entry(name).or_insert_with(|| CString::new(path.as_os_str().as_bytes()).unwrap());
+        }
+
+        for (name, path) in found {
+            let entry = if let Some(entry) = self.entries.get(&(ino, name.clone())) {
+                *entry
+            } else {
+                self.ino += 1;
+                self.entries.insert((ino, name.clone()), self.ino);
+                self.rec_by_ino.insert(self.ino, ino);
+                self.ino
+            };
+
+            query.by_name.insert(name, (entry, path));
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       attr()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn attr(&self, ino: u64) -> Option<FileAttr> {
+        let (kind, nlink, updated) = if ino == INO {
+            (FileType::Directory, 1 + self.by_name.len() as u32, UNIX_EPOCH)
+        } else if let Some(query) = self.by_ino.get(&ino) {
+            (FileType::Directory, 1 + query.by_name.len() as u32, query.updated)
+        } else if self.rec_by_ino.contains_key(&ino) {
+            (FileType::Symlink, 1, UNIX_EPOCH)
+        } else {
+            return None;
+        };
+
+        Some(FileAttr {
+            ino,
+            size: 0,
+            blocks: 0,
+            atime: UNIX_EPOCH,
+            mtime: updated,
+            ctime: updated,
+            crtime: UNIX_EPOCH,
+            kind,
+            perm: 0o555,
+            nlink,
+            uid: 0,
+            gid: 0,
+            rdev: 0,
+            blksize: 0,
+            padding: 0,
+            flags: 0,
+        })
+    }
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                           Query                                            │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+struct Query {
+    regex: Regex,
+    by_name: BTreeMap<String, (u64, CString)>,
+    updated: SystemTime,
+    used: SystemTime,
+}
@@ -0,0 +1,721 @@
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                                                                            │ *
+ * │ This Source Code Form is subject to the terms of the Mozilla Public                        │ *
+ * │ License, v. 2.0. If a copy of the MPL was not distributed with this                        │ *
+ * │ file, You can obtain one at http://mozilla.org/MPL/2.0/.                                   │ *
+ * │                                                                                            │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                            use                                             │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+use super::{TTL, ROOT_INO};
+use super::artifacts::Artifacts;
+use super::mirrors::Mirrors;
+use super::projects::Projects;
+use fuser::{FileAttr, FileType, ReplyAttr, ReplyData, ReplyDirectory, ReplyEntry, ReplyXattr};
+use libc::{ENODATA, ENOENT, ERANGE};
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, Weak};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                           const                                            │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+pub const INO: u64 = 1 << 45;
+pub const STR: &str = "usage";
+
+const MIRRORS: &str = "mirrors";
+const TOP: &str = "top";
+
+const MIRRORS_INO: u64 = INO + 1;
+const TOP_INO: u64 = INO + 2;
+
+const SIZE: &str = "user.size";
+const BLOCKS: &str = "user.st_blocks";
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                           Usage                                            │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+// Projects named `mirrors` or `top` are shadowed by the directories of the same name.
+pub struct Usage {
+    ino: u64,
+    by_name: HashMap<(u64, String), u64>,
+    by_ino: HashMap<u64, Node>,
+    owners: Vec<Owner>,
+    targets: Vec<Target>,
+    top: usize,
+    updated: SystemTime,
+    shared: Arc<Mutex<Shared>>,
+    spawned: bool,
+}
+
+impl Usage {
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       new()                                        │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn new() -> Self {
+        let shared = Arc::new(Mutex::new(Shared {
+            paths: Vec::new(),
+            sizes: HashMap::new(),
+            budget: 2_000,
+            interval: Duration::from_secs(600),
+        }));
+
+        Usage {
+            ino: TOP_INO,
+            by_name: HashMap::new(),
+            by_ino: HashMap::new(),
+            owners: Vec::new(),
+            targets: Vec::new(),
+            top: 20,
+            updated: UNIX_EPOCH,
+            shared,
+            spawned: false,
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     inherit()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn inherit(&mut self, old: &mut Self) {
+        // The measuring thread and what it measured so far survive reloads, instead of walking
+        // everything again next to a thread that's still busy with the old paths.
+        self.ino = self.ino.max(old.ino);
+        self.spawned = old.spawned;
+        std::mem::swap(&mut self.shared, &mut old.shared);
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                    configure()                                     │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn configure(&mut self, budget: usize, interval: Duration, top: usize) {
+        let mut shared = self.shared.lock().unwrap();
+        shared.budget = budget.max(1);
+        shared.interval = interval;
+        self.top = top;
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      update()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn update(
+        &mut self,
+        projects: &Projects,
+        artifacts: &Artifacts,
+        mirrors: &mut Mirrors,
+    ) {
+        if self.updated.elapsed().unwrap_or_default() < TTL {
+            return;
+        }
+
+        self.updated = SystemTime::now();
+        self.spawn();
+        self.owners.clear();
+        self.targets.clear();
+
+        for (name, path) in projects.iter() {
+            let owner = Owner::Project(name.to_string());
+            self.owners.push(owner.clone());
+            self.targets.push(Target {
+                owner: owner.clone(),
+                label: name.to_string(),
+                path: path.to_path_buf(),
+            });
+
+            let target = artifacts.target_dir(path);
+            if target.is_dir() {
+                let dir = target.file_name().map(|dir| dir.to_string_lossy().into_owned());
+                self.targets.push(Target {
+                    owner,
+                    label: format!("{}@{}", dir.unwrap_or_else(|| "target".into()), name),
+                    path: target,
+                });
+            }
+        }
+
+        mirrors.update();
+        for (mirror, entries) in mirrors.index() {
+            let owner = Owner::Mirror(mirror.to_string());
+            self.owners.push(owner.clone());
+
+            for (entry, path) in entries {
+                self.targets.push(Target {
+                    owner: owner.clone(),
+                    label: format!("{}@{}", entry.replace('/', ":"), mirror),
+                    path: path.to_path_buf(),
+                });
+            }
+        }
+
+        let mut paths = self.targets.iter().map(|target| target.path.clone()).collect::<Vec<_>>();
+        paths.sort_unstable();
+        paths.dedup();
+
+        let mut shared = self.shared.lock().unwrap();
+        shared.sizes.retain(|path, _| paths.binary_search(path).is_ok());
+        shared.paths = paths;
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      spawn()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    // The thread only holds a weak reference, so that it exits once the namespace is dropped.
+    fn spawn(&mut self) {
+        if self.spawned {
+            return;
+        }
+
+        self.spawned = true;
+        let weak = Arc::downgrade(&self.shared);
+        if let Err(err) = thread::Builder::new().name(STR.into()).spawn(move || work(weak)) {
+            log::warn!("failed to spawn the disk usage thread: {}", err);
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     getattr()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn getattr(&self, ino: u64, reply: ReplyAttr) {
+        if let Some(attr) = self.attr(ino) {
+            reply.attr(&TTL, &attr);
+        } else {
+            reply.error(ENOENT);
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      lookup()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn lookup(
+        &mut self,
+        projects: &Projects,
+        artifacts: &Artifacts,
+        mirrors: &mut Mirrors,
+        parent: u64,
+        name: &str,
+        reply: ReplyEntry,
+    ) {
+        if parent == ROOT_INO {
+            return reply.entry(&TTL, &self.attr(INO).unwrap(), 0);
+        }
+
+        self.update(projects, artifacts, mirrors);
+
+        let ino = match (parent, name) {
+            (INO, MIRRORS) => MIRRORS_INO,
+            (INO, TOP) => TOP_INO,
+            _ => match self.children(parent).into_iter().find(|(entry, _)| entry == name) {
+                Some((name, node)) => self.add_node(parent, &name, node),
+                None => return reply.error(ENOENT),
+            },
+        };
+
+        reply.entry(&TTL, &self.attr(ino).unwrap(), 0);
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     readdir()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn readdir(
+        &mut self,
+        projects: &Projects,
+        artifacts: &Artifacts,
+        mirrors: &mut Mirrors,
+        ino: u64,
+        offset: usize,
+        mut reply: ReplyDirectory,
+    ) {
+        if ino != INO && ino != MIRRORS_INO && ino != TOP_INO {
+            return reply.error(ENOENT);
+        } else if offset == 0 {
+            self.update(projects, artifacts, mirrors);
+        }
+
+        let mut entries = Vec::new();
+        if ino == INO {
+            entries.push((MIRRORS_INO, FileType::Directory, MIRRORS.to_string()));
+            entries.push((TOP_INO, FileType::Directory, TOP.to_string()));
+        }
+
+        for (name, node) in self.children(ino) {
+            let kind = node.kind();
+            entries.push((self.add_node(ino, &name, node), kind, name));
+        }
+
+        for (idx, (ino, kind, name)) in entries.into_iter().enumerate().skip(offset) {
+            if reply.add(ino, (idx + 1) as i64, kind, name) {
+                break;
+            }
+        }
+
+        reply.ok();
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     readlink()                                     │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn readlink(&self, ino: u64, reply: ReplyData) {
+        match self.by_ino.get(&ino) {
+            Some(Node::Top(path)) => reply.data(path.as_os_str().as_bytes()),
+            _ => reply.error(ENOENT),
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       read()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn read(&self, ino: u64, offset: i64, size: u32, reply: ReplyData) {
+        match self.by_ino.get(&ino) {
+            Some(Node::Report(owner)) => {
+                let data = self.report(owner).into_bytes();
+                let start = (offset.max(0) as usize).min(data.len());
+                let end = start.saturating_add(size as usize).min(data.len());
+                reply.data(&data[start..end]);
+            },
+            _ => reply.error(ENOENT),
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     getxattr()                                     │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn getxattr(&self, ino: u64, name: &str, size: u32, reply: ReplyXattr) {
+        let usage = match self.by_ino.get(&ino).and_then(|node| self.size(node)) {
+            Some(usage) => usage,
+            None => return reply.error(ENODATA),
+        };
+
+        let value = match name {
+            SIZE => usage.bytes,
+            BLOCKS => usage.blocks,
+            _ => return reply.error(ENODATA),
+        };
+
+        xattr(value.to_string().as_bytes(), size, reply);
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                    listxattr()                                     │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn listxattr(&self, ino: u64, size: u32, reply: ReplyXattr) {
+        match self.by_ino.get(&ino).and_then(|node| self.size(node)) {
+            Some(_) => xattr(format!("{}\0{}\0", SIZE, BLOCKS).as_bytes(), size, reply),
+            None => xattr(&[], size, reply),
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     children()                                     │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn children(&self, ino: u64) -> Vec<(String, Node)> {
+        match ino {
+            INO => self
+                .owners
+                .iter()
+                .filter_map(|owner| match owner {
+                    Owner::Project(name) => Some((name.clone(), Node::Report(owner.clone()))),
+                    Owner::Mirror(_) => None,
+                })
+                .collect(),
+            MIRRORS_INO => self
+                .owners
+                .iter()
+                .filter_map(|owner| match owner {
+                    Owner::Mirror(name) => Some((name.clone(), Node::Report(owner.clone()))),
+                    Owner::Project(_) => None,
+                })
+                .collect(),
+            TOP_INO => self.top(),
+            _ => Vec::new(),
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       top()                                        │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    // Entries are prefixed with their rank, so that they list in order. Mirror aliases point to
+    // the same paths as their mirror, only the first label of a path is kept.
+    fn top(&self) -> Vec<(String, Node)> {
+        let shared = self.shared.lock().unwrap();
+
+        let mut seen = HashSet::new();
+        let mut top = self
+            .targets
+            .iter()
+            .filter(|target| seen.insert(&target.path))
+            .filter_map(|target| Some((target, shared.sizes.get(&target.path)?.blocks)))
+            .collect::<Vec<_>>();
+        top.sort_by_key(|(_, blocks)| Reverse(*blocks));
+        top.truncate(self.top);
+
+        let width = top.len().to_string().len();
+        top.into_iter()
+            .enumerate()
+            .map(|(idx, (target, _))| {
+                let name = format!("{:0width$}-{}", idx + 1, target.label, width = width);
+                (name, Node::Top(target.path.clone()))
+            })
+            .collect()
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      report()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    // Formatted like `du -h`, entries that haven't been measured yet show up as `?`.
+    fn report(&self, owner: &Owner) -> String {
+        let shared = self.shared.lock().unwrap();
+
+        let mut lines = self
+            .targets
+            .iter()
+            .filter(|target| target.owner == *owner)
+            .map(|target| (shared.sizes.get(&target.path).map(|size| size.blocks), &target.path))
+            .collect::<Vec<_>>();
+        lines.sort_by_key(|(blocks, _)| Reverse(*blocks));
+
+        lines
+            .into_iter()
+            .map(|(blocks, path)| {
+                let size = blocks.map(|blocks| human(blocks * 512)).unwrap_or_else(|| "?".into());
+                format!("{}\t{}\n", size, path.display())
+            })
+            .collect()
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       size()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    // A project's usage is the one of its directory, a mirror's is the sum of its entries'.
+    fn size(&self, node: &Node) -> Option<Size> {
+        let shared = self.shared.lock().unwrap();
+
+        match node {
+            Node::Report(owner @ Owner::Project(name)) => self
+                .targets
+                .iter()
+                .find(|target| target.owner == *owner && target.label == *name)
+                .and_then(|target| shared.sizes.get(&target.path).copied()),
+            Node::Report(owner @ Owner::Mirror(_)) => self
+                .targets
+                .iter()
+                .filter(|target| target.owner == *owner)
+                .filter_map(|target| shared.sizes.get(&target.path))
+                .fold(None, |total: Option<Size>, size| {
+                    let total = total.unwrap_or_default();
+                    Some(Size {
+                        bytes: total.bytes + size.bytes,
+                        blocks: total.blocks + size.blocks,
+                    })
+                }),
+            Node::Top(path) => shared.sizes.get(path).copied(),
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     add_node()                                     │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn add_node(&mut self, parent: u64, name: &str, node: Node) -> u64 {
+        let ino = if let Some(ino) = self.by_name.get(&(parent, name.to_string())) {
+            *ino
+        } else {
+            self.ino += 1;
+            self.by_name.insert((parent, name.to_string()), self.ino);
+            self.ino
+        };
+
+        self.by_ino.insert(ino, node);
+
+        ino
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       attr()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    // Files report the measured usage as their `st_blocks`, so that `du` works on them too.
+    fn attr(&self, ino: u64) -> Option<FileAttr> {
+        let (kind, size, blocks, perm) = match self.by_ino.get(&ino) {
+            _ if ino == INO || ino == MIRRORS_INO || ino == TOP_INO => {
+                (FileType::Directory, 0, 0, 0o555)
+            },
+            Some(node @ Node::Report(owner)) => {
+                let blocks = self.size(node).map(|size| size.blocks).unwrap_or(0);
+                (FileType::RegularFile, self.report(owner).len() as u64, blocks, 0o444)
+            },
+            Some(node @ Node::Top(path)) => {
+                let blocks = self.size(node).map(|size| size.blocks).unwrap_or(0);
+                (FileType::Symlink, path.as_os_str().len() as u64, blocks, 0o555)
+            },
+            None => return None,
+        };
+
+        Some(FileAttr {
+            ino,
+            size,
+            blocks,
+            atime: UNIX_EPOCH,
+            mtime: UNIX_EPOCH,
+            ctime: UNIX_EPOCH,
+            crtime: UNIX_EPOCH,
+            kind,
+            perm,
+            nlink: if kind == FileType::Directory { 2 } else { 1 },
+            uid: 0,
+            gid: 0,
+            rdev: 0,
+            blksize: 0,
+            padding: 0,
+            flags: 0,
+        })
+    }
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                           Shared                                           │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+struct Shared {
+    paths: Vec<PathBuf>,
+    sizes: HashMap<PathBuf, Size>,
+    budget: usize,
+    interval: Duration,
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                            Size                                            │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+#[derive(Clone, Copy, Default)]
+struct Size {
+    bytes: u64,
+    blocks: u64,
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                           Owner                                            │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+#[derive(Clone, PartialEq)]
+enum Owner {
+    Project(String),
+    Mirror(String),
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                           Target                                           │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+struct Target {
+    owner: Owner,
+    label: String,
+    path: PathBuf,
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                            Node                                            │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+enum Node {
+    Report(Owner),
+    Top(PathBuf),
+}
+
+impl Node {
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       kind()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn kind(&self) -> FileType {
+        match self {
+            Node::Report(_) => FileType::RegularFile,
+            Node::Top(_) => FileType::Symlink,
+        }
+    }
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                           Budget                                           │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+// Limits how many entries get `stat`ed per second.
+struct Budget {
+    limit: usize,
+    spent: usize,
+    since: Instant,
+}
+
+impl Budget {
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      spend()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn spend(&mut self) {
+        self.spent += 1;
+        if self.spent < self.limit {
+            return;
+        }
+
+        let elapsed = self.since.elapsed();
+        if elapsed < Duration::from_secs(1) {
+            thread::sleep(Duration::from_secs(1) - elapsed);
+        }
+
+        self.spent = 0;
+        self.since = Instant::now();
+    }
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                           work()                                           │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+// Measures every path once per `interval`, paths that were never measured are picked up within a
+// second.
+fn work(shared: Weak<Mutex<Shared>>) {
+    let mut measured = HashMap::<PathBuf, Instant>::new();
+
+    loop {
+        let (paths, budget, interval) = match shared.upgrade() {
+            Some(shared) => {
+                let shared = shared.lock().unwrap();
+                (shared.paths.clone(), shared.budget, shared.interval)
+            },
+            None => return,
+        };
+
+        measured.retain(|path, _| paths.binary_search(path).is_ok());
+        let due = paths
+            .into_iter()
+            .filter(|path| !matches!(measured.get(path), Some(at) if at.elapsed() < interval))
+            .collect::<Vec<_>>();
+
+        if due.is_empty() {
+            thread::sleep(Duration::from_secs(1));
+            continue;
+        }
+
+        let mut budget = Budget { limit: budget, spent: 0, since: Instant::now() };
+        for path in due {
+            let size = measure(&path, &mut budget);
+            match shared.upgrade() {
+                Some(shared) => {
+                    let mut shared = shared.lock().unwrap();
+                    if shared.paths.binary_search(&path).is_ok() {
+                        shared.sizes.insert(path.clone(), size);
+                    }
+                },
+                None => return,
+            }
+
+            measured.insert(path, Instant::now());
+        }
+    }
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                         measure()                                          │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+// Symlinks aren't followed and hard links are only counted once, `target/` is full of them.
+fn measure(path: &Path, budget: &mut Budget) -> Size {
+    let mut size = Size::default();
+    let mut linked = HashSet::new();
+    let mut dirs = vec![path.to_path_buf()];
+
+    while let Some(dir) = dirs.pop() {
+        budget.spend();
+        let metadata = match fs::symlink_metadata(&dir) {
+            Ok(metadata) => metadata,
+            Err(_) => continue,
+        };
+
+        size.bytes += metadata.len();
+        size.blocks += metadata.blocks();
+        if !metadata.is_dir() {
+            continue;
+        }
+
+        for entry in fs::read_dir(&dir).into_iter().flatten().filter_map(Result::ok) {
+            let metadata = match entry.metadata() {
+                Ok(metadata) if metadata.is_dir() => {
+                    dirs.push(entry.path());
+                    continue;
+                },
+                Ok(metadata) => metadata,
+                Err(_) => continue,
+            };
+
+            budget.spend();
+            if metadata.nlink() > 1 && !linked.insert((metadata.dev(), metadata.ino())) {
+                continue;
+            }
+
+            size.bytes += metadata.len();
+            size.blocks += metadata.blocks();
+        }
+    }
+
+    size
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                          human()                                           │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+fn human(bytes: u64) -> String {
+    if bytes < 1024 {
+        return bytes.to_string();
+    }
+
+    let mut size = bytes as f64 / 1024.0;
+    for unit in &["K", "M", "G", "T"] {
+        if size < 1024.0 {
+            return format!("{:.1}{}", size, unit);
+        }
+
+        size /= 1024.0;
+    }
+
+    format!("{:.1}P", size)
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                          xattr()                                           │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+fn xattr(data: &[u8], size: u32, reply: ReplyXattr) {
+    if size == 0 {
+        reply.size(data.len() as u32);
+    } else if data.len() > size as usize {
+        reply.error(ERANGE);
+    } else {
+        reply.data(data);
+    }
+}
@@ -0,0 +1,188 @@
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                                                                            │ *
+ * │ This Source Code Form is subject to the terms of the Mozilla Public                        │ *
+ * │ License, v. 2.0. If a copy of the MPL was not distributed with this                        │ *
+ * │ file, You can obtain one at http://mozilla.org/MPL/2.0/.                                   │ *
+ * │                                                                                            │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                            use                                             │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+use libc::{c_int, inotify_event, EAGAIN};
+use std::collections::{HashMap, HashSet};
+use std::ffi::CString;
+use std::io;
+use std::mem;
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                           const                                            │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+const MASK: u32 = libc::IN_CREATE
+    | libc::IN_DELETE
+    | libc::IN_MOVED_FROM
+    | libc::IN_MOVED_TO
+    | libc::IN_DELETE_SELF
+    | libc::IN_MOVE_SELF
+    | libc::IN_ONLYDIR;
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                          Watcher                                           │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+// Directories are watched on behalf of keys (mirrors), several keys can share a directory, in
+// which case they share the watch descriptor too.
+pub struct Watcher {
+    fd: c_int,
+    by_wd: HashMap<c_int, HashSet<u64>>,
+    by_key: HashMap<u64, HashMap<PathBuf, c_int>>,
+}
+
+impl Watcher {
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       new()                                        │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn new() -> io::Result<Self> {
+        let fd = unsafe { libc::inotify_init1(libc::IN_NONBLOCK | libc::IN_CLOEXEC) };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(Watcher {
+            fd,
+            by_wd: HashMap::new(),
+            by_key: HashMap::new(),
+        })
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      watch()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    // Replaces the directories watched for `key` with `paths`.
+    pub fn watch<'a>(&mut self, key: u64, paths: impl Iterator<Item = &'a Path>) {
+        let mut old = self.by_key.remove(&key).unwrap_or_default();
+        let mut new = HashMap::new();
+
+        for path in paths {
+            if let Some(wd) = old.remove(path) {
+                new.insert(path.to_path_buf(), wd);
+                continue;
+            } else if new.contains_key(path) {
+                continue;
+            }
+
+            let cpath = match CString::new(path.as_os_str().as_bytes()) {
+                Ok(cpath) => cpath,
+                Err(_) => continue,
+            };
+
+            let wd = unsafe { libc::inotify_add_watch(self.fd, cpath.as_ptr(), MASK) };
+            if wd < 0 {
+                log::warn!("failed to watch {}: {}", path.display(), io::Error::last_os_error());
+                continue;
+            }
+
+            self.by_wd.entry(wd).or_default().insert(key);
+            new.insert(path.to_path_buf(), wd);
+        }
+
+        for (_, wd) in old {
+            self.unwatch(key, wd);
+        }
+
+        self.by_key.insert(key, new);
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       poll()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    // Drains the pending events without blocking, returning the keys they concern. Once the queue
+    // overflowed, events got lost and every key is returned.
+    pub fn poll(&mut self) -> HashSet<u64> {
+        let mut keys = HashSet::new();
+        let mut buf = [0u64; 512];
+
+        loop {
+            let len = unsafe {
+                libc::read(self.fd, buf.as_mut_ptr() as *mut _, mem::size_of_val(&buf))
+            };
+
+            if len <= 0 {
+                let err = io::Error::last_os_error();
+                if len < 0 && err.raw_os_error() != Some(EAGAIN) {
+                    log::warn!("failed to read inotify events: {}", err);
+                }
+
+                break;
+            }
+
+            let bytes = unsafe {
+                std::slice::from_raw_parts(buf.as_ptr() as *const u8, len as usize)
+            };
+            let mut offset = 0;
+            while offset + mem::size_of::<inotify_event>() <= bytes.len() {
+                let event = unsafe {
+                    (bytes.as_ptr().add(offset) as *const inotify_event).read_unaligned()
+                };
+                offset += mem::size_of::<inotify_event>() + event.len as usize;
+
+                if event.mask & libc::IN_Q_OVERFLOW != 0 {
+                    log::warn!("inotify queue overflowed, treating every watch as changed");
+                    keys.extend(self.by_key.keys());
+                    continue;
+                }
+
+                if let Some(watchers) = self.by_wd.get(&event.wd) {
+                    keys.extend(watchers);
+                }
+
+                // The kernel already dropped the watch, the directory got deleted or unmounted.
+                if event.mask & libc::IN_IGNORED != 0 {
+                    for key in self.by_wd.remove(&event.wd).unwrap_or_default() {
+                        if let Some(paths) = self.by_key.get_mut(&key) {
+                            paths.retain(|_, wd| *wd != event.wd);
+                        }
+                    }
+                }
+            }
+        }
+
+        keys
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     unwatch()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn unwatch(&mut self, key: u64, wd: c_int) {
+        let unused = match self.by_wd.get_mut(&wd) {
+            Some(keys) => {
+                keys.remove(&key);
+                keys.is_empty()
+            },
+            None => false,
+        };
+
+        if unused {
+            self.by_wd.remove(&wd);
+            unsafe { libc::inotify_rm_watch(self.fd, wd) };
+        }
+    }
+}
+
+impl Drop for Watcher {
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       drop()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn drop(&mut self) {
+        unsafe { libc::close(self.fd) };
+    }
+}
@@ -0,0 +1,495 @@
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                                                                            │ *
+ * │ This Source Code Form is subject to the terms of the Mozilla Public                        │ *
+ * │ License, v. 2.0. If a copy of the MPL was not distributed with this                        │ *
+ * │ file, You can obtain one at http://mozilla.org/MPL/2.0/.                                   │ *
+ * │                                                                                            │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                            use                                             │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+use super::{TTL, ROOT_INO};
+use super::passthrough::{self, Passthrough};
+use super::projects::Projects;
+use fuser::{
+    FileAttr, FileType,
+    ReplyAttr, ReplyCreate, ReplyData, ReplyDirectory, ReplyEmpty, ReplyEntry, ReplyWrite,
+    TimeOrNow,
+};
+use git2::{BranchType, ErrorCode, Repository, WorktreeAddOptions};
+use libc::{EACCES, EEXIST, EINVAL, EIO, ENOENT};
+use std::collections::HashMap;
+use std::ffi::{CString, OsStr};
+use std::fs;
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                           const                                            │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+pub const INO: u64 = 1 << 56;
+pub const STR: &str = "worktrees";
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                         Worktrees                                          │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+pub struct Worktrees {
+    ino: u64,
+    location: Option<PathBuf>,
+    by_name: HashMap<(u64, String), u64>,
+    by_ino: HashMap<u64, Node>,
+    passthrough: Passthrough,
+}
+
+impl Worktrees {
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       new()                                        │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn new() -> Self {
+        Worktrees {
+            ino: INO,
+            location: None,
+            by_name: HashMap::new(),
+            by_ino: HashMap::new(),
+            passthrough: Passthrough::new(),
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     inherit()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn inherit(&mut self, old: &mut Self) {
+        self.ino = self.ino.max(old.ino);
+        std::mem::swap(&mut self.passthrough, &mut old.passthrough);
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                    configure()                                     │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn configure(&mut self, location: Option<PathBuf>) {
+        self.location = location;
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     getattr()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn getattr(&self, ino: u64, reply: ReplyAttr) {
+        if let Some(path) = self.passthrough.path(ino) {
+            passthrough::getattr(ino, path, reply);
+        } else if let Some(attr) = self.attr(ino) {
+            reply.attr(&TTL, &attr);
+        } else {
+            reply.error(ENOENT);
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      lookup()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn lookup(&mut self, projects: &Projects, parent: u64, name: &OsStr, reply: ReplyEntry) {
+        if let Some(path) = self.path(parent) {
+            return self.passthrough.lookup(&mut self.ino, &path, name, reply);
+        }
+
+        let name = match name.to_str() {
+            Some(name) => name,
+            None => return reply.error(ENOENT),
+        };
+
+        let ino = if parent == ROOT_INO {
+            INO
+        } else if parent == INO {
+            match projects.get(name) {
+                Some(path) if Repository::open(path).is_ok() => {
+                    self.add_node(INO, name, Node::Project(name.to_string()))
+                },
+                _ => return reply.error(ENOENT),
+            }
+        } else if let Some(Node::Project(project)) = self.by_ino.get(&parent) {
+            let worktree = projects
+                .get(project)
+                .and_then(worktrees)
+                .and_then(|worktrees| worktrees.into_iter().find(|(branch, _)| branch == name));
+
+            match worktree {
+                Some((_, path)) => self.add_node(parent, name, Node::Worktree(cstring(&path))),
+                None => return reply.error(ENOENT),
+            }
+        } else {
+            return reply.error(ENOENT);
+        };
+
+        reply.entry(&TTL, &self.attr(ino).unwrap(), 0);
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      forget()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn forget(&mut self, ino: u64, nlookup: u64) {
+        self.passthrough.forget(ino, nlookup);
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     readdir()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn readdir(&mut self, projects: &Projects, ino: u64, offset: usize, mut reply: ReplyDirectory) {
+        if let Some(path) = self.path(ino) {
+            return self.passthrough.readdir(&path, offset, reply);
+        } else if ino == INO {
+            let mut names = projects
+                .iter()
+                .filter(|(_, path)| Repository::open(path).is_ok())
+                .map(|(name, _)| name.to_string())
+                .collect::<Vec<_>>();
+            names.sort_unstable();
+
+            for (idx, name) in names.into_iter().enumerate().skip(offset) {
+                let ino = self.add_node(INO, &name, Node::Project(name.clone()));
+                if reply.add(ino, (idx + 1) as i64, FileType::Directory, name) {
+                    break;
+                }
+            }
+        } else if let Some(Node::Project(project)) = self.by_ino.get(&ino) {
+            let worktrees = projects.get(project).and_then(worktrees).unwrap_or_default();
+
+            for (idx, (branch, path)) in worktrees.into_iter().enumerate().skip(offset) {
+                let entry = self.add_node(ino, &branch, Node::Worktree(cstring(&path)));
+                if reply.add(entry, (idx + 1) as i64, FileType::Symlink, branch) {
+                    break;
+                }
+            }
+        } else {
+            return reply.error(ENOENT);
+        }
+
+        reply.ok();
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     readlink()                                     │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn readlink(&self, ino: u64, reply: ReplyData) {
+        match self.by_ino.get(&ino) {
+            Some(Node::Worktree(path)) => reply.data(path.as_bytes()),
+            _ => match self.passthrough.path(ino) {
+                Some(path) => passthrough::readlink(path, reply),
+                None => reply.error(ENOENT),
+            },
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       read()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn read(&self, ino: u64, offset: i64, size: u32, reply: ReplyData) {
+        match self.passthrough.path(ino) {
+            Some(path) => passthrough::read(path, offset, size, reply),
+            None => reply.error(ENOENT),
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      write()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn write(&self, ino: u64, offset: i64, data: &[u8], reply: ReplyWrite) {
+        match self.passthrough.path(ino) {
+            Some(path) => passthrough::write(path, offset, data, reply),
+            None => reply.error(EACCES),
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     setattr()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn setattr(
+        &self,
+        ino: u64,
+        mode: Option<u32>,
+        size: Option<u64>,
+        times: (Option<TimeOrNow>, Option<TimeOrNow>),
+        reply: ReplyAttr,
+    ) {
+        match self.path(ino) {
+            Some(path) => passthrough::setattr(ino, &path, mode, size, times, reply),
+            None => reply.error(EACCES),
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      create()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn create(&mut self, parent: u64, name: &OsStr, mode: u32, reply: ReplyCreate) {
+        match self.path(parent) {
+            Some(path) => self.passthrough.create(&mut self.ino, &path, name, mode, reply),
+            None => reply.error(EACCES),
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      mkdir()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn mkdir(
+        &mut self,
+        projects: &Projects,
+        parent: u64,
+        name: &OsStr,
+        mode: u32,
+        reply: ReplyEntry,
+    ) {
+        if let Some(path) = self.path(parent) {
+            return self.passthrough.mkdir(&mut self.ino, &path, name, mode, reply);
+        }
+
+        let name = match name.to_str() {
+            Some(name) => name,
+            None => return reply.error(EINVAL),
+        };
+
+        let project = match self.by_ino.get(&parent) {
+            Some(Node::Project(project)) => project.clone(),
+            _ => return reply.error(EACCES),
+        };
+
+        let path = match projects.get(&project) {
+            Some(path) => path,
+            None => return reply.error(ENOENT),
+        };
+
+        let location = match &self.location {
+            Some(location) => location.join(&project),
+            None => {
+                let mut location = path.as_os_str().to_owned();
+                location.push(".worktrees");
+                PathBuf::from(location)
+            },
+        };
+
+        match add(path, &name.replace(':', "/"), &location.join(name)) {
+            // The kernel expects a directory back, so the new checkout is handed out as a
+            // passthrough directory while later lookups see the usual symlink.
+            Ok(path) => {
+                self.add_node(parent, name, Node::Worktree(cstring(&path)));
+                match (path.parent(), path.file_name()) {
+                    (Some(dir), Some(name)) => {
+                        self.passthrough.lookup(&mut self.ino, dir, name, reply)
+                    },
+                    _ => reply.error(EIO),
+                }
+            },
+            Err(err) if err.code() == ErrorCode::Exists => reply.error(EEXIST),
+            Err(err) if err.code() == ErrorCode::NotFound => reply.error(ENOENT),
+            Err(err) => {
+                log::warn!("failed to create worktree {} for {}: {}", name, project, err);
+                reply.error(EIO);
+            },
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      unlink()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn unlink(&mut self, parent: u64, name: &OsStr, reply: ReplyEmpty) {
+        match self.path(parent) {
+            Some(path) => self.passthrough.unlink(&path, name, reply),
+            None => reply.error(EACCES),
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      rmdir()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn rmdir(&mut self, parent: u64, name: &OsStr, reply: ReplyEmpty) {
+        match self.path(parent) {
+            Some(path) => self.passthrough.rmdir(&path, name, reply),
+            None => reply.error(EACCES),
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                      rename()                                      │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    pub fn rename(
+        &mut self,
+        parent: u64,
+        name: &OsStr,
+        new_parent: u64,
+        new_name: &OsStr,
+        reply: ReplyEmpty,
+    ) {
+        match (self.path(parent), self.path(new_parent)) {
+            (Some(path), Some(new_path)) => {
+                self.passthrough.rename(&path, name, &new_path, new_name, reply)
+            },
+            _ => reply.error(EACCES),
+        }
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       path()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn path(&self, ino: u64) -> Option<PathBuf> {
+        self.passthrough.path(ino).map(Path::to_path_buf)
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                     add_node()                                     │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn add_node(&mut self, parent: u64, name: &str, node: Node) -> u64 {
+        let ino = if let Some(ino) = self.by_name.get(&(parent, name.to_string())) {
+            *ino
+        } else {
+            self.ino += 1;
+            self.by_name.insert((parent, name.to_string()), self.ino);
+            self.ino
+        };
+
+        self.by_ino.insert(ino, node);
+
+        ino
+    }
+
+/*     ┌────────────────────────────────────────────────────────────────────────────────────┐     *\
+ *     │                                       attr()                                       │     *
+\*     └────────────────────────────────────────────────────────────────────────────────────┘     */
+
+    fn attr(&self, ino: u64) -> Option<FileAttr> {
+        let kind = match self.by_ino.get(&ino) {
+            _ if ino == INO => FileType::Directory,
+            Some(Node::Project(_)) => FileType::Directory,
+            Some(Node::Worktree(_)) => FileType::Symlink,
+            None => return None,
+        };
+
+        Some(FileAttr {
+            ino,
+            size: 0,
+            blocks: 0,
+            atime: UNIX_EPOCH,
+            mtime: UNIX_EPOCH,
+            ctime: UNIX_EPOCH,
+            crtime: UNIX_EPOCH,
+            kind,
+            perm: if kind == FileType::Directory { 0o755 } else { 0o555 },
+            nlink: if kind == FileType::Directory { 2 } else { 1 },
+            uid: 0,
+            gid: 0,
+            rdev: 0,
+            blksize: 0,
+            padding: 0,
+            flags: 0,
+        })
+    }
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                            Node                                            │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+enum Node {
+    Project(String),
+    Worktree(CString),
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                        worktrees()                                         │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+fn worktrees(path: &Path) -> Option<Vec<(String, PathBuf)>> {
+    let repo = Repository::open(path).ok()?;
+
+    // A main worktree whose HEAD can't be resolved (e.g. unborn) doesn't hide the linked ones.
+    let mut worktrees = Vec::new();
+    if let (Some(workdir), Some(branch)) = (repo.workdir(), branch(&repo)) {
+        worktrees.push((branch, workdir.components().collect()));
+    }
+
+    for name in repo.worktrees().ok()?.iter().flatten() {
+        let worktree = match repo.find_worktree(name) {
+            Ok(worktree) => worktree,
+            Err(_) => continue,
+        };
+
+        if let Some(branch) = Repository::open(worktree.path()).ok().as_ref().and_then(branch) {
+            worktrees.push((branch, worktree.path().components().collect()));
+        }
+    }
+
+    Some(worktrees)
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                          branch()                                          │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+fn branch(repo: &Repository) -> Option<String> {
+    let head = repo.head().ok()?;
+
+    if head.is_branch() {
+        Some(head.shorthand()?.replace('/', ":"))
+    } else {
+        let oid = head.target()?.to_string();
+        Some(oid[..7].to_string())
+    }
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                           add()                                            │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+fn add(path: &Path, branch: &str, location: &Path) -> Result<PathBuf, git2::Error> {
+    let repo = Repository::open(path)?;
+
+    let branch = match repo.find_branch(branch, BranchType::Local) {
+        Ok(branch) => branch,
+        Err(err) if err.code() == ErrorCode::NotFound => {
+            let start = match repo.find_branch(&format!("origin/{}", branch), BranchType::Remote) {
+                Ok(remote) => remote.get().peel_to_commit()?,
+                Err(_) => repo.head()?.peel_to_commit()?,
+            };
+
+            repo.branch(branch, &start, false)?
+        },
+        Err(err) => return Err(err),
+    };
+
+    if let Some(parent) = location.parent() {
+        fs::create_dir_all(parent).map_err(|err| git2::Error::from_str(&err.to_string()))?;
+    }
+
+    let name = location.file_name().unwrap().to_str().unwrap();
+    let reference = branch.into_reference();
+    let worktree = repo.worktree(name, location, Some(WorktreeAddOptions::new().reference(Some(&reference))))?;
+
+    Ok(worktree.path().components().collect())
+}
+
+/* ┌────────────────────────────────────────────────────────────────────────────────────────────┐ *\
+ * │                                         cstring()                                          │ *
+\* └────────────────────────────────────────────────────────────────────────────────────────────┘ */
+
+fn cstring(path: &Path) -> CString {
+    CString::new(path.as_os_str().as_bytes()).unwrap()
+}